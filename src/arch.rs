@@ -6,14 +6,41 @@ mod value {
     use std::fmt::Debug;
     use std::ops::{Add, Sub};
 
+    use num::traits::{WrappingAdd, WrappingSub};
+    use num::PrimInt;
+
     /// Architecture supported integer data types.
+    ///
+    /// This trait is sealed, and is implemented only for the primitive
+    /// integers. Memory models rely on this to soundly view their contents as
+    /// raw bytes.
     pub trait Value:
-        Add<Output = Self> + Copy + Debug + Default + Eq + Ord + Sub<Output = Self>
+        Sealed
+        + Add<Output = Self>
+        + Copy
+        + Debug
+        + Default
+        + Eq
+        + Ord
+        + PrimInt
+        + Send
+        + Sub<Output = Self>
+        + Sync
+        + WrappingAdd
+        + WrappingSub
+        + 'static
     {
     }
 
+    /// Prevents downstream implementations of [`Value`].
+    ///
+    /// As this trait is not nameable outside of the crate, it cannot be
+    /// implemented for any foreign type.
+    pub trait Sealed {}
+
     macro_rules! add_impl {
         ($($t:ty)*) => ($(
+            impl Sealed for $t {}
             impl Value for $t {}
         )*)
     }
@@ -62,6 +89,238 @@ where
     }
 }
 
+/// Access context.
+///
+/// Describes the nature of an access and the bus master which initiated it,
+/// allowing devices to respond differently to each. Contexts are passed
+/// through [`Device::try_read_ctx`](crate::dev::Device::try_read_ctx) and
+/// [`Device::try_write_ctx`](crate::dev::Device::try_write_ctx).
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Context {
+    /// Kind of access.
+    pub access: Access,
+    /// Identifier of the bus master which initiated the access.
+    pub initiator: usize,
+}
+
+impl Context {
+    /// Constructs a new `Context`.
+    #[must_use]
+    pub fn new(access: Access, initiator: usize) -> Self {
+        Self { access, initiator }
+    }
+}
+
+/// Access kind.
+///
+/// Shared by [access contexts](Context) and [bus tracing](crate::bus::trace).
+///
+/// # Note
+///
+/// The kind of an access should agree with its direction. Reads are either
+/// [`Read`](Self::Read), [`Fetch`](Self::Fetch), [`Dma`](Self::Dma) or
+/// [`Debug`](Self::Debug), whereas writes are either [`Write`](Self::Write),
+/// `Dma` or `Debug`.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Access {
+    /// Data read.
+    #[default]
+    Read,
+    /// Data write.
+    Write,
+    /// Instruction fetch.
+    Fetch,
+    /// Direct memory access transfer.
+    ///
+    /// Allows devices to model regions which are inaccessible to DMA, while
+    /// remaining accessible to the CPU.
+    Dma,
+    /// Debugger access.
+    ///
+    /// Debug accesses must not have side effects, and are performed through
+    /// [`Device::peek`](crate::dev::Device::peek) and
+    /// [`Device::poke`](crate::dev::Device::poke).
+    Debug,
+}
+
+/// Declares multi-width accessors built on byte-wide reads.
+macro_rules! wide_read {
+    ($($name:ident -> $t:ty => $conv:ident, $endian:literal;)*) => {$(
+        #[doc = concat!("Reads a ", $endian, " [`", stringify!($t), "`] from the specified address.")]
+        fn $name(&self, index: Idx) -> $t {
+            <$t>::$conv(self.read_bytes(index))
+        }
+    )*};
+}
+
+/// Declares multi-width accessors built on byte-wide writes.
+macro_rules! wide_write {
+    ($($name:ident <- $t:ty => $conv:ident, $endian:literal;)*) => {$(
+        #[doc = concat!("Writes a ", $endian, " [`", stringify!($t), "`] to the specified address.")]
+        fn $name(&mut self, index: Idx, value: $t) {
+            self.write_bytes(index, value.$conv());
+        }
+    )*};
+}
+
+/// Declares fallible multi-width accessors built on byte-wide reads.
+macro_rules! try_wide_read {
+    ($($name:ident -> $t:ty => $conv:ident, $endian:literal;)*) => {$(
+        #[doc = concat!("Fallibly reads a ", $endian, " [`", stringify!($t), "`] from the specified address.")]
+        ///
+        /// # Errors
+        ///
+        /// Errors if any byte-wide read is not successful.
+        fn $name(&self, index: Idx) -> Result<$t, Self::Error> {
+            self.try_read_bytes(index).map(<$t>::$conv)
+        }
+    )*};
+}
+
+/// Declares fallible multi-width accessors built on byte-wide writes.
+macro_rules! try_wide_write {
+    ($($name:ident <- $t:ty => $conv:ident, $endian:literal;)*) => {$(
+        #[doc = concat!("Fallibly writes a ", $endian, " [`", stringify!($t), "`] to the specified address.")]
+        ///
+        /// # Errors
+        ///
+        /// Errors if any byte-wide write is not successful.
+        fn $name(&mut self, index: Idx, value: $t) -> Result<(), Self::Error> {
+            self.try_write_bytes(index, value.$conv())
+        }
+    )*};
+}
+
+/// Multi-width addressable read-write interface.
+///
+/// Extends byte-addressable [`Address`] implementors with accessors for wider
+/// integers in either endianness. Consecutive bytes are accessed at increasing
+/// addresses, wrapping around at the top of the index space.
+pub trait AddressExt<Idx>: Address<Idx, u8>
+where
+    Idx: Value,
+{
+    /// Reads `N` consecutive bytes starting at the specified address.
+    fn read_bytes<const N: usize>(&self, index: Idx) -> [u8; N] {
+        let mut buf = [0; N];
+        let mut index = index;
+        for byte in &mut buf {
+            *byte = self.read(index);
+            index = index.wrapping_add(&Idx::one());
+        }
+        buf
+    }
+
+    /// Writes `N` consecutive bytes starting at the specified address.
+    fn write_bytes<const N: usize>(&mut self, index: Idx, bytes: [u8; N]) {
+        let mut index = index;
+        for byte in bytes {
+            self.write(index, byte);
+            index = index.wrapping_add(&Idx::one());
+        }
+    }
+
+    wide_read! {
+        read_u16_le -> u16 => from_le_bytes, "little-endian";
+        read_u16_be -> u16 => from_be_bytes, "big-endian";
+        read_u32_le -> u32 => from_le_bytes, "little-endian";
+        read_u32_be -> u32 => from_be_bytes, "big-endian";
+        read_u64_le -> u64 => from_le_bytes, "little-endian";
+        read_u64_be -> u64 => from_be_bytes, "big-endian";
+    }
+
+    wide_write! {
+        write_u16_le <- u16 => to_le_bytes, "little-endian";
+        write_u16_be <- u16 => to_be_bytes, "big-endian";
+        write_u32_le <- u32 => to_le_bytes, "little-endian";
+        write_u32_be <- u32 => to_be_bytes, "big-endian";
+        write_u64_le <- u64 => to_le_bytes, "little-endian";
+        write_u64_be <- u64 => to_be_bytes, "big-endian";
+    }
+}
+
+impl<T, Idx> AddressExt<Idx> for T
+where
+    T: Address<Idx, u8> + ?Sized,
+    Idx: Value,
+{
+}
+
+/// Multi-width fallible addressable read-write interface.
+///
+/// Extends byte-addressable [`TryAddress`] implementors with fallible
+/// accessors for wider integers in either endianness. Consecutive bytes are
+/// accessed at increasing addresses, wrapping around at the top of the index
+/// space.
+///
+/// # Note
+///
+/// Accesses stop at the first unsuccessful byte. As such, a failed write may
+/// have been partially performed.
+pub trait TryAddressExt<Idx>: TryAddress<Idx, u8>
+where
+    Idx: Value,
+{
+    /// Fallibly reads `N` consecutive bytes starting at the specified address.
+    ///
+    /// # Errors
+    ///
+    /// Errors if any byte-wide read is not successful.
+    fn try_read_bytes<const N: usize>(&self, index: Idx) -> Result<[u8; N], Self::Error> {
+        let mut buf = [0; N];
+        let mut index = index;
+        for byte in &mut buf {
+            *byte = self.try_read(index)?;
+            index = index.wrapping_add(&Idx::one());
+        }
+        Ok(buf)
+    }
+
+    /// Fallibly writes `N` consecutive bytes starting at the specified
+    /// address.
+    ///
+    /// # Errors
+    ///
+    /// Errors if any byte-wide write is not successful.
+    fn try_write_bytes<const N: usize>(
+        &mut self,
+        index: Idx,
+        bytes: [u8; N],
+    ) -> Result<(), Self::Error> {
+        let mut index = index;
+        for byte in bytes {
+            self.try_write(index, byte)?;
+            index = index.wrapping_add(&Idx::one());
+        }
+        Ok(())
+    }
+
+    try_wide_read! {
+        try_read_u16_le -> u16 => from_le_bytes, "little-endian";
+        try_read_u16_be -> u16 => from_be_bytes, "big-endian";
+        try_read_u32_le -> u32 => from_le_bytes, "little-endian";
+        try_read_u32_be -> u32 => from_be_bytes, "big-endian";
+        try_read_u64_le -> u64 => from_le_bytes, "little-endian";
+        try_read_u64_be -> u64 => from_be_bytes, "big-endian";
+    }
+
+    try_wide_write! {
+        try_write_u16_le <- u16 => to_le_bytes, "little-endian";
+        try_write_u16_be <- u16 => to_be_bytes, "big-endian";
+        try_write_u32_le <- u32 => to_le_bytes, "little-endian";
+        try_write_u32_be <- u32 => to_be_bytes, "big-endian";
+        try_write_u64_le <- u64 => to_le_bytes, "little-endian";
+        try_write_u64_be <- u64 => to_be_bytes, "big-endian";
+    }
+}
+
+impl<T, Idx> TryAddressExt<Idx> for T
+where
+    T: TryAddress<Idx, u8> + ?Sized,
+    Idx: Value,
+{
+}
+
 /// Register load-store interface.
 pub trait Cell<V>
 where
@@ -90,3 +349,54 @@ where
     /// Stores to the specified register.
     fn store(&mut self, reg: Self::Register, value: V);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::{Bus, Mux};
+    use crate::dev::Device;
+    use crate::mem::Ram;
+
+    #[test]
+    fn address_ext_read_works() {
+        let ram = Ram::from(&[0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef]);
+        assert_eq!(ram.read_u16_le(0usize), 0x2301);
+        assert_eq!(ram.read_u16_be(0usize), 0x0123);
+        assert_eq!(ram.read_u32_le(4usize), 0xefcd_ab89);
+        assert_eq!(ram.read_u32_be(4usize), 0x89ab_cdef);
+        assert_eq!(ram.read_u64_le(0usize), 0xefcd_ab89_6745_2301);
+        assert_eq!(ram.read_u64_be(0usize), 0x0123_4567_89ab_cdef);
+    }
+
+    #[test]
+    fn address_ext_write_works() {
+        let mut ram = Ram::<u8, 8>::new();
+        ram.write_u16_le(0usize, 0x2301);
+        ram.write_u16_be(2usize, 0x4567);
+        ram.write_u32_le(4usize, 0xefcd_ab89);
+        assert_eq!(ram.read_u64_be(0usize), 0x0123_4567_89ab_cdef);
+        ram.write_u64_le(0usize, 0x0011_2233_4455_6677);
+        assert_eq!(ram.read_u32_be(0usize), 0x7766_5544);
+    }
+
+    #[test]
+    fn address_ext_wrapping_works() {
+        let mut bus = Bus::<u8, u8>::new();
+        bus.map(0x00..=0xff, Ram::<u8, 0x100>::new().to_dynamic());
+        bus.write_u16_le(0xff, 0xbbaa);
+        assert_eq!(bus.read(0xff), 0xaa);
+        assert_eq!(bus.read(0x00), 0xbb);
+        assert_eq!(bus.read_u16_be(0xff), 0xaabb);
+    }
+
+    #[test]
+    fn try_address_ext_works() {
+        let mut ram = Ram::<u8, 4>::new();
+        assert!(ram.try_write_u32_be(0usize, 0xdead_beef).is_ok());
+        assert_eq!(ram.try_read_u32_be(0usize).ok(), Some(0xdead_beef));
+        assert_eq!(ram.try_read_u16_le(2usize).ok(), Some(0xefbe));
+        // Accesses past the end of the device should fail
+        assert!(ram.try_read_u16_le(3usize).is_err());
+        assert!(ram.try_write_u64_le(0usize, 0).is_err());
+    }
+}
@@ -1,6 +1,8 @@
 use std::fmt::Debug;
 
 use crate::share::Shared;
+#[cfg(feature = "state")]
+use crate::state;
 
 /// Integrated circuit block.
 pub trait Block: Debug {
@@ -18,6 +20,30 @@ pub trait Block: Debug {
     ///       of the emulator, accessing persistent data after a reset may be
     ///       considered undefined behaviour.
     fn reset(&mut self) {}
+
+    /// Saves the state of this [`Block`].
+    ///
+    /// # Note
+    ///
+    /// The provided implementation saves nothing. Implementors with state
+    /// should override this alongside [`Block::load_state`].
+    #[cfg(feature = "state")]
+    fn save_state(&self, _state: &mut state::Writer) {}
+
+    /// Loads the state of this [`Block`].
+    ///
+    /// # Errors
+    ///
+    /// Errors if the recorded state is malformed, or does not match the shape
+    /// of this block.
+    ///
+    /// # Note
+    ///
+    /// The provided implementation loads nothing.
+    #[cfg(feature = "state")]
+    fn load_state(&mut self, _state: &mut state::Reader) -> Result<(), state::Error> {
+        Ok(())
+    }
 }
 
 /// Linked [`Block`] instance.
@@ -1,6 +1,10 @@
-use crate::arch::{Address, Value};
+use std::ops::RangeInclusive;
+
+use crate::arch::{Address, Context, Value};
 use crate::blk::Block;
-use crate::dev::{Device, Dynamic};
+use crate::dev::{self, Device, Dynamic};
+#[cfg(feature = "state")]
+use crate::state;
 
 /// Device bank.
 ///
@@ -95,6 +99,31 @@ where
             bank.reset();
         }
     }
+
+    #[cfg(feature = "state")]
+    fn save_state(&self, state: &mut state::Writer) {
+        state.section("bank", 1, |state| {
+            state.value(u32::try_from(self.sel).expect("bank selection too large"));
+            state.count(self.vec.len());
+            for dev in &self.vec {
+                dev.save_state(state);
+            }
+        });
+    }
+
+    #[cfg(feature = "state")]
+    fn load_state(&mut self, state: &mut state::Reader) -> Result<(), state::Error> {
+        state.section("bank", 1, |state, _| {
+            let sel: u32 = state.value()?;
+            state::expect(self.vec.len(), state.count()?)?;
+            state::bound(sel.into(), self.vec.len() as u64)?;
+            self.sel = sel as usize;
+            for dev in &mut self.vec {
+                dev.load_state(state)?;
+            }
+            Ok(())
+        })
+    }
 }
 
 impl<Idx, V> Device<Idx, V> for Bank<Idx, V>
@@ -102,6 +131,53 @@ where
     Idx: Value,
     V: Value,
 {
+    fn try_read_dyn(&self, index: Idx) -> Result<V, dev::Error> {
+        self.vec[self.sel].try_read_dyn(index)
+    }
+
+    fn try_write_dyn(&mut self, index: Idx, value: V) -> Result<(), dev::Error> {
+        self.vec[self.sel].try_write_dyn(index, value)
+    }
+
+    fn try_read_ctx(&self, index: Idx, ctx: Context) -> Result<V, dev::Error> {
+        self.vec[self.sel].try_read_ctx(index, ctx)
+    }
+
+    fn try_write_ctx(&mut self, index: Idx, value: V, ctx: Context) -> Result<(), dev::Error> {
+        self.vec[self.sel].try_write_ctx(index, value, ctx)
+    }
+
+    fn try_read_block(&self, index: Idx, buf: &mut [V]) -> Result<(), dev::Error> {
+        self.vec[self.sel].try_read_block(index, buf)
+    }
+
+    fn try_write_block(&mut self, index: Idx, buf: &[V]) -> Result<(), dev::Error> {
+        self.vec[self.sel].try_write_block(index, buf)
+    }
+
+    /// Returns the dirty ranges of the selected device.
+    fn dirty(&self) -> Vec<RangeInclusive<usize>> {
+        self.vec[self.sel].dirty()
+    }
+
+    /// Marks all offsets of the selected device as clean.
+    ///
+    /// # Note
+    ///
+    /// As with [`Device::dirty`], only the selected device is affected.
+    /// Unselected devices retain their dirty ranges until selected and
+    /// cleaned.
+    fn clean(&mut self) {
+        self.vec[self.sel].clean();
+    }
+
+    fn peek(&self, index: Idx) -> V {
+        self.vec[self.sel].peek(index)
+    }
+
+    fn poke(&mut self, index: Idx, value: V) {
+        self.vec[self.sel].poke(index, value);
+    }
 }
 
 impl<Idx, V> From<&[Dynamic<Idx, V>]> for Bank<Idx, V>
@@ -169,4 +245,23 @@ mod tests {
             .map(|index| bank.read(index))
             .any(|value| value != 0xaa));
     }
+
+    #[test]
+    fn device_dirty_works() {
+        let mut bank = Bank::<usize, u8>::new();
+        for _ in 0..2 {
+            let mut ram = Ram::<u8, 0x100>::new();
+            ram.track(4);
+            bank.add(ram.to_dynamic());
+        }
+        bank.write(0x00, 0xaa);
+        bank.set(1);
+        bank.write(0x10, 0xbb);
+        // Only the selected device is reported and cleaned
+        assert_eq!(bank.dirty(), [0x10..=0x1f]);
+        bank.clean();
+        assert!(bank.dirty().is_empty());
+        bank.set(0);
+        assert_eq!(bank.dirty(), [0x00..=0x0f]);
+    }
 }
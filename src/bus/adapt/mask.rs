@@ -1,9 +1,12 @@
 use std::marker::PhantomData;
+use std::ops::{Range, RangeInclusive};
 
-use crate::arch::{TryAddress, Value};
+use crate::arch::{Context, TryAddress, Value};
 use crate::blk::Block;
 use crate::bus::{self, Mux};
-use crate::dev::Device;
+use crate::dev::{self, Device};
+#[cfg(feature = "state")]
+use crate::state;
 use crate::Address;
 
 /// Bus mask.
@@ -11,6 +14,13 @@ use crate::Address;
 /// # Usage
 ///
 /// The `Mask` adapter...
+///
+/// # Errors
+///
+/// Accesses fall through to lower layers only where a layer is
+/// [unmapped](bus::Error::Unmapped). Any other error, such as a protection
+/// fault or a device error, is returned from the first layer that is mapped,
+/// without consulting the layers beneath it.
 #[derive(Debug)]
 pub struct Mask<T, Idx, V>(Vec<T>, PhantomData<(Idx, V)>)
 where
@@ -73,6 +83,34 @@ where
     pub fn reverse(&mut self) {
         self.0.reverse();
     }
+
+    /// Splits a block into runs of indices resolved by the same layer.
+    ///
+    /// Returns the position of each run's layer, its starting index, and its
+    /// span within the block.
+    ///
+    /// # Errors
+    ///
+    /// Errors if any index is unmapped within every layer.
+    #[allow(clippy::type_complexity)]
+    fn runs(&self, index: Idx, len: usize) -> Result<Vec<(usize, Idx, Range<usize>)>, Error<Idx>> {
+        let mut runs: Vec<(usize, Idx, Range<usize>)> = Vec::new();
+        let mut idx = index;
+        for at in 0..len {
+            // Fall through layers until one is mapped
+            let layer = self
+                .0
+                .iter()
+                .position(|layer| layer.get(idx).is_some())
+                .ok_or(Error::Unmapped(idx))?;
+            match runs.last_mut() {
+                Some((last, _, span)) if *last == layer => span.end += 1,
+                _ => runs.push((layer, idx, at..at + 1)),
+            }
+            idx = idx.wrapping_add(&Idx::one());
+        }
+        Ok(runs)
+    }
 }
 
 impl<T, Idx, V> Address<Idx, V> for Mask<T, Idx, V>
@@ -99,17 +137,25 @@ where
     type Error = Error<Idx>;
 
     fn try_read(&self, index: Idx) -> Result<V, Self::Error> {
-        self.0
-            .iter()
-            .find_map(|layer| layer.try_read(index).ok())
-            .ok_or(Error::Unmapped(index))
+        // Fall through layers until one is mapped
+        for layer in &self.0 {
+            let res = layer.try_read(index);
+            if !matches!(res, Err(Error::Unmapped(_))) {
+                return res;
+            }
+        }
+        Err(Error::Unmapped(index))
     }
 
     fn try_write(&mut self, index: Idx, value: V) -> Result<(), Self::Error> {
-        self.0
-            .iter_mut()
-            .find_map(|layer| layer.try_write(index, value).ok())
-            .ok_or(Error::Unmapped(index))
+        // Fall through layers until one is mapped
+        for layer in &mut self.0 {
+            let res = layer.try_write(index, value);
+            if !matches!(res, Err(Error::Unmapped(_))) {
+                return res;
+            }
+        }
+        Err(Error::Unmapped(index))
     }
 }
 
@@ -119,6 +165,26 @@ where
     Idx: Value,
     V: Value,
 {
+    #[cfg(feature = "state")]
+    fn save_state(&self, state: &mut state::Writer) {
+        state.section("mask", 1, |state| {
+            state.count(self.0.len());
+            for layer in &self.0 {
+                layer.save_state(state);
+            }
+        });
+    }
+
+    #[cfg(feature = "state")]
+    fn load_state(&mut self, state: &mut state::Reader) -> Result<(), state::Error> {
+        state.section("mask", 1, |state, _| {
+            state::expect(self.0.len(), state.count()?)?;
+            for layer in &mut self.0 {
+                layer.load_state(state)?;
+            }
+            Ok(())
+        })
+    }
 }
 
 impl<T, Idx, V> Default for Mask<T, Idx, V>
@@ -138,6 +204,69 @@ where
     Idx: Value,
     V: Value,
 {
+    fn try_read_dyn(&self, index: Idx) -> Result<V, dev::Error> {
+        self.try_read(index).map_err(Into::into)
+    }
+
+    fn try_write_dyn(&mut self, index: Idx, value: V) -> Result<(), dev::Error> {
+        self.try_write(index, value).map_err(Into::into)
+    }
+
+    fn try_read_ctx(&self, index: Idx, ctx: Context) -> Result<V, dev::Error> {
+        // Fall through layers until one is mapped
+        match self.0.iter().find(|layer| layer.get(index).is_some()) {
+            Some(layer) => layer.try_read_ctx(index, ctx),
+            None => Err(Error::Unmapped(index).into()),
+        }
+    }
+
+    fn try_write_ctx(&mut self, index: Idx, value: V, ctx: Context) -> Result<(), dev::Error> {
+        // Fall through layers until one is mapped
+        match self.0.iter_mut().find(|layer| layer.get(index).is_some()) {
+            Some(layer) => layer.try_write_ctx(index, value, ctx),
+            None => Err(Error::Unmapped(index).into()),
+        }
+    }
+
+    fn try_read_block(&self, index: Idx, buf: &mut [V]) -> Result<(), dev::Error> {
+        for (layer, idx, span) in self.runs(index, buf.len())? {
+            self.0[layer].try_read_block(idx, &mut buf[span])?;
+        }
+        Ok(())
+    }
+
+    fn try_write_block(&mut self, index: Idx, buf: &[V]) -> Result<(), dev::Error> {
+        for (layer, idx, span) in self.runs(index, buf.len())? {
+            self.0[layer].try_write_block(idx, &buf[span])?;
+        }
+        Ok(())
+    }
+
+    fn dirty(&self) -> Vec<RangeInclusive<usize>> {
+        bus::coalesce(self.0.iter().flat_map(Device::dirty).collect())
+    }
+
+    fn clean(&mut self) {
+        for layer in &mut self.0 {
+            layer.clean();
+        }
+    }
+
+    fn peek(&self, index: Idx) -> V {
+        // Fall through layers until one is mapped
+        match self.0.iter().find(|layer| layer.get(index).is_some()) {
+            Some(layer) => layer.peek(index),
+            None => panic!("`<Mask as Device>::peek`: {}", Error::Unmapped(index)),
+        }
+    }
+
+    fn poke(&mut self, index: Idx, value: V) {
+        // Fall through layers until one is mapped
+        match self.0.iter_mut().find(|layer| layer.get(index).is_some()) {
+            Some(layer) => layer.poke(index, value),
+            None => panic!("`<Mask as Device>::poke`: {}", Error::Unmapped(index)),
+        }
+    }
 }
 
 /// A type specifying general categories of [`Mask`] error.
@@ -206,6 +335,29 @@ mod tests {
         });
     }
 
+    #[test]
+    fn block_full_works() {
+        let mut mask = setup_full();
+        // Blocks are split across layers
+        let mut buf = [0; 0x40];
+        mask.read_block(0x10, &mut buf);
+        assert_eq!(buf[..0x10], [0xaa; 0x10]);
+        assert_eq!(buf[0x10..0x30], [0xbb; 0x20]);
+        assert_eq!(buf[0x30..], [0xcc; 0x10]);
+        mask.write_block(0x18, &[0x11; 0x10]);
+        assert_eq!(mask.layer(4).unwrap().read(0x1f), 0x11);
+        assert_eq!(mask.layer(3).unwrap().read(0x20), 0x11);
+        assert_eq!(mask.layer(4).unwrap().read(0x20), 0xaa);
+    }
+
+    #[test]
+    fn probe_full_works() {
+        let mut mask = setup_full();
+        assert_eq!(mask.peek(0x30), 0xbb);
+        mask.poke(0x30, 0x55);
+        assert_eq!(mask.read(0x30), 0x55);
+        assert_eq!(mask.layer(3).unwrap().read(0x30), 0x55);
+    }
 
     fn setup_holy() -> Mask<Bus, u16, u8> {
         // Create a new mask
@@ -275,6 +427,27 @@ mod tests {
         mask
     }
 
+    #[test]
+    fn test_device_error_works() {
+        let mut mask = setup_holy();
+        // Shadow the first layer with a smaller device
+        let mut bus = Bus::new();
+        bus.map(0x00..=0x3f, Ram::from(&[0xdd; 0x20]).to_dynamic());
+        mask.insert(0, bus);
+        // Device errors should not fall through to lower layers
+        assert_eq!(mask.read(0x1f), 0xdd);
+        assert!(matches!(
+            mask.try_read(0x20),
+            Err(bus::Error::Device { index: 0x20, .. })
+        ));
+        assert!(matches!(
+            mask.try_write(0x20, 0x00),
+            Err(bus::Error::Device { index: 0x20, .. })
+        ));
+        // Unmapped indices should still fall through
+        assert_eq!(mask.read(0x60), 0xbb);
+    }
+
     #[test]
     fn test_real_works() {
         let mask = setup_real();
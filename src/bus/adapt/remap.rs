@@ -2,10 +2,13 @@
 #![allow(clippy::cast_sign_loss)]
 
 use std::marker::PhantomData;
+use std::ops::RangeInclusive;
 
-use crate::arch::{Address, Value};
+use crate::arch::{Address, Context, Value};
 use crate::blk::Block;
-use crate::dev::Device;
+use crate::dev::{self, Device};
+#[cfg(feature = "state")]
+use crate::state;
 
 /// Address remap.
 ///
@@ -71,6 +74,16 @@ where
     fn reset(&mut self) {
         self.dev.reset();
     }
+
+    #[cfg(feature = "state")]
+    fn save_state(&self, state: &mut state::Writer) {
+        self.dev.save_state(state);
+    }
+
+    #[cfg(feature = "state")]
+    fn load_state(&mut self, state: &mut state::Reader) -> Result<(), state::Error> {
+        self.dev.load_state(state)
+    }
 }
 
 impl<T, Idx, V> Device<Idx, V> for Remap<T, Idx, V>
@@ -79,6 +92,58 @@ where
     Idx: Value,
     V: Value,
 {
+    fn try_read_dyn(&self, index: Idx) -> Result<V, dev::Error> {
+        let index = index - self.off;
+        self.dev.try_read_dyn(index)
+    }
+
+    fn try_write_dyn(&mut self, index: Idx, value: V) -> Result<(), dev::Error> {
+        let index = index - self.off;
+        self.dev.try_write_dyn(index, value)
+    }
+
+    fn try_read_ctx(&self, index: Idx, ctx: Context) -> Result<V, dev::Error> {
+        let index = index - self.off;
+        self.dev.try_read_ctx(index, ctx)
+    }
+
+    fn try_write_ctx(&mut self, index: Idx, value: V, ctx: Context) -> Result<(), dev::Error> {
+        let index = index - self.off;
+        self.dev.try_write_ctx(index, value, ctx)
+    }
+
+    fn try_read_block(&self, index: Idx, buf: &mut [V]) -> Result<(), dev::Error> {
+        let index = index - self.off;
+        self.dev.try_read_block(index, buf)
+    }
+
+    fn try_write_block(&mut self, index: Idx, buf: &[V]) -> Result<(), dev::Error> {
+        let index = index - self.off;
+        self.dev.try_write_block(index, buf)
+    }
+
+    fn dirty(&self) -> Vec<RangeInclusive<usize>> {
+        let off = self.off.to_usize().unwrap_or_default();
+        self.dev
+            .dirty()
+            .into_iter()
+            .map(|it| it.start().saturating_add(off)..=it.end().saturating_add(off))
+            .collect()
+    }
+
+    fn clean(&mut self) {
+        self.dev.clean();
+    }
+
+    fn peek(&self, index: Idx) -> V {
+        let index = index - self.off;
+        self.dev.peek(index)
+    }
+
+    fn poke(&mut self, index: Idx, value: V) {
+        let index = index - self.off;
+        self.dev.poke(index, value);
+    }
 }
 
 #[cfg(test)]
@@ -115,4 +180,17 @@ mod tests {
             assert_eq!(ram.read(index), 0x00);
         });
     }
+
+    #[test]
+    fn device_block_works() {
+        let ram = Ram::<u8, 0x100>::new().to_dynamic();
+        let mut remap: Remap<_, usize, u8> = Remap::new(0x080, ram.clone());
+        remap.write_block(0x090, &[0xaa; 0x10]);
+        let mut buf = [0; 0x10];
+        ram.read_block(0x010, &mut buf);
+        assert_eq!(buf, [0xaa; 0x10]);
+        remap.read_block(0x088, &mut buf);
+        assert_eq!(buf[..0x08], [0x00; 0x08]);
+        assert_eq!(buf[0x08..], [0xaa; 0x08]);
+    }
 }
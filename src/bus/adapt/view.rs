@@ -1,12 +1,15 @@
 use std::fmt::Debug;
 use std::marker::PhantomData;
+use std::ops::RangeInclusive;
 
 use thiserror::Error;
 
-use crate::arch::{Address, TryAddress, Value};
+use crate::arch::{Address, Context, TryAddress, Value};
 use crate::blk::Block;
 use crate::bus::Range;
-use crate::dev::Device;
+use crate::dev::{self, Device};
+#[cfg(feature = "state")]
+use crate::state;
 
 /// Partial address view.
 ///
@@ -43,6 +46,16 @@ where
             phantom: PhantomData,
         }
     }
+
+    /// Offsets a block of `len` elements, checking it lies within the view.
+    fn bounds(&self, index: Idx, len: usize) -> Result<Idx, Error<Idx>> {
+        let offset = index + *self.range.start();
+        Idx::from(len.saturating_sub(1))
+            .and_then(|len| offset.checked_add(&len))
+            .filter(|last| len == 0 || self.range.contains(&offset) && self.range.contains(last))
+            .map(|_| offset)
+            .ok_or(Error::Block { index, len })
+    }
 }
 
 impl<T, Idx, V> Address<Idx, V> for View<T, Idx, V>
@@ -96,6 +109,16 @@ where
     fn reset(&mut self) {
         self.dev.reset();
     }
+
+    #[cfg(feature = "state")]
+    fn save_state(&self, state: &mut state::Writer) {
+        self.dev.save_state(state);
+    }
+
+    #[cfg(feature = "state")]
+    fn load_state(&mut self, state: &mut state::Reader) -> Result<(), state::Error> {
+        self.dev.load_state(state)
+    }
 }
 
 impl<T, Idx, V> Device<Idx, V> for View<T, Idx, V>
@@ -104,6 +127,83 @@ where
     Idx: Value,
     V: Value,
 {
+    fn try_read_dyn(&self, index: Idx) -> Result<V, dev::Error> {
+        let offset = index + *self.range.start();
+        if !self.range.contains(&offset) {
+            return Err(Error::Bounds(index).into());
+        }
+        self.dev.try_read_dyn(offset)
+    }
+
+    fn try_write_dyn(&mut self, index: Idx, value: V) -> Result<(), dev::Error> {
+        let offset = index + *self.range.start();
+        if !self.range.contains(&offset) {
+            return Err(Error::Bounds(index).into());
+        }
+        self.dev.try_write_dyn(offset, value)
+    }
+
+    fn try_read_ctx(&self, index: Idx, ctx: Context) -> Result<V, dev::Error> {
+        let offset = index + *self.range.start();
+        if !self.range.contains(&offset) {
+            return Err(Error::Bounds(index).into());
+        }
+        self.dev.try_read_ctx(offset, ctx)
+    }
+
+    fn try_write_ctx(&mut self, index: Idx, value: V, ctx: Context) -> Result<(), dev::Error> {
+        let offset = index + *self.range.start();
+        if !self.range.contains(&offset) {
+            return Err(Error::Bounds(index).into());
+        }
+        self.dev.try_write_ctx(offset, value, ctx)
+    }
+
+    fn try_read_block(&self, index: Idx, buf: &mut [V]) -> Result<(), dev::Error> {
+        let offset = self.bounds(index, buf.len())?;
+        self.dev.try_read_block(offset, buf)
+    }
+
+    fn try_write_block(&mut self, index: Idx, buf: &[V]) -> Result<(), dev::Error> {
+        let offset = self.bounds(index, buf.len())?;
+        self.dev.try_write_block(offset, buf)
+    }
+
+    fn dirty(&self) -> Vec<RangeInclusive<usize>> {
+        let (Some(start), Some(end)) = (self.range.start().to_usize(), self.range.end().to_usize())
+        else {
+            return Vec::new();
+        };
+        // Clip to within the view
+        self.dev
+            .dirty()
+            .into_iter()
+            .filter(|it| *it.start() <= end && start <= *it.end())
+            .map(|it| *it.start().max(&start) - start..=*it.end().min(&end) - start)
+            .collect()
+    }
+
+    fn clean(&mut self) {
+        self.dev.clean();
+    }
+
+    fn peek(&self, index: Idx) -> V {
+        let offset = index + *self.range.start();
+        assert!(
+            self.range.contains(&offset),
+            "`<View as Device>::peek`: index out of bounds: {index:?}"
+        );
+        self.dev.peek(offset)
+    }
+
+    fn poke(&mut self, index: Idx, value: V) {
+        let offset = index + *self.range.start();
+        assert!(
+            self.range.contains(&offset),
+            "`<View as Device>::poke`: index out of bounds: {index:?}"
+        );
+        self.dev.poke(offset, value);
+    }
 }
 
 /// A type specifying general categories of [`View`] error.
@@ -111,6 +211,13 @@ where
 pub enum Error<Idx: Value> {
     #[error("index out of bounds: {0:?}")]
     Bounds(Idx),
+    #[error("block out of bounds: {index:?} (len: {len})")]
+    Block {
+        /// Starting index of the block.
+        index: Idx,
+        /// Length of the block.
+        len: usize,
+    },
 }
 
 #[cfg(test)]
@@ -157,4 +264,25 @@ mod tests {
             assert_eq!(ram.read(index), 0x00);
         });
     }
+
+    #[test]
+    fn device_block_works() {
+        let ram: Dynamic<usize, u8> = Ram::<u8, 0x100>::new().to_dynamic();
+        let mut view = View::new(0x40..=0xbf, ram.clone());
+        view.write_block(0x00, &[0xaa; 0x80]);
+        let mut buf = [0; 0x100];
+        ram.read_block(0x00, &mut buf);
+        assert_eq!(buf[..0x40], [0x00; 0x40]);
+        assert_eq!(buf[0x40..0xc0], [0xaa; 0x80]);
+        assert_eq!(buf[0xc0..], [0x00; 0x40]);
+    }
+
+    #[test]
+    #[should_panic(expected = "block out of bounds")]
+    fn device_block_out_of_bounds_panics() {
+        let ram: Dynamic<usize, u8> = Ram::<u8, 0x100>::new().to_dynamic();
+        let view = View::new(0x40..=0xbf, ram);
+        let mut buf = [0; 0x10];
+        view.read_block(0x78, &mut buf);
+    }
 }
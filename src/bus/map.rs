@@ -1,8 +1,11 @@
 use std::cmp::Ordering;
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fmt::Debug;
+use std::ops::Bound::{Excluded, Unbounded};
 use std::ops::RangeInclusive;
 
+use super::page::{Page, Table};
+use super::{Decode, Perm};
 use crate::arch::Value;
 
 type Range<Idx> = RangeInclusive<Idx>;
@@ -12,10 +15,16 @@ pub(super) trait Entry: Clone + Debug + Eq {}
 impl<T> Entry for T where T: Clone + Debug + Eq {}
 
 #[derive(Debug)]
-pub(super) struct Map<Idx, V>(BTreeMap<Idx, BTreeSet<Mapping<Idx, V>>>)
+pub(super) struct Map<Idx, V>
 where
     Idx: Value,
-    V: Entry;
+    V: Entry,
+{
+    tree: BTreeMap<Idx, BTreeSet<Mapping<Idx, V>>>,
+    page: Table<Idx, V>,
+    tags: HashMap<usize, String>,
+    next: usize,
+}
 
 impl<Idx, V> Map<Idx, V>
 where
@@ -27,42 +36,197 @@ where
         Self::default()
     }
 
+    pub(super) fn with_granularity(bits: u32) -> Self {
+        Self {
+            tree: BTreeMap::default(),
+            page: Table::with_granularity(bits),
+            tags: HashMap::default(),
+            next: 0,
+        }
+    }
+
     pub(super) fn clear(&mut self) {
-        self.0.clear();
+        self.tree.clear();
+        self.page.clear();
+        self.tags.clear();
+    }
+
+    pub(super) fn map(&mut self, range: Range<Idx>, entry: V) -> usize {
+        self.map_with(range, entry, Decode::Full)
     }
 
-    pub(super) fn map(&mut self, range: Range<Idx>, entry: V) {
-        let new = Mapping::new(range, entry);
-        self.0.entry(new.base()).or_default().insert(new);
+    pub(super) fn map_with(&mut self, range: Range<Idx>, entry: V, decode: Decode<Idx>) -> usize {
+        let id = self.next;
+        self.next += 1;
+        self.insert(Mapping {
+            decode,
+            ..Mapping::new(id, range, entry)
+        });
+        id
     }
 
     pub(super) fn unmap(&mut self, entry: &V) -> Option<V> {
         // TODO: Implement using `extract_if` to bypass extraneous `clone`
         let found = self.find(entry)?.clone();
-        self.0
-            .get_mut(&found.base())?
-            .take(&found)
-            .map(|it| it.entry)
+        self.take(&found)
+    }
+
+    pub(super) fn unmap_id(&mut self, id: usize) -> Option<V> {
+        let found = self.lookup(id)?.clone();
+        self.take(&found)
+    }
+
+    pub(super) fn unmap_range(&mut self, range: &Range<Idx>) -> Vec<V> {
+        let found: Vec<_> = self
+            .tree
+            .range(range.clone())
+            .flat_map(|(_, maps)| maps.iter())
+            .filter(|it| it.range.end() <= range.end())
+            .cloned()
+            .collect();
+        found.iter().filter_map(|it| self.take(it)).collect()
+    }
+
+    pub(super) fn remap(&mut self, id: usize, range: Range<Idx>) -> bool {
+        self.modify(id, |it| it.range = range)
+    }
+
+    pub(super) fn label(&mut self, id: usize, label: Option<String>) -> bool {
+        if self.lookup(id).is_none() {
+            return false;
+        }
+        match label {
+            Some(label) => self.tags.insert(id, label),
+            None => self.tags.remove(&id),
+        };
+        true
+    }
+
+    pub(super) fn label_of(&self, id: usize) -> Option<&String> {
+        self.tags.get(&id)
+    }
+
+    pub(super) fn protect(&mut self, id: usize, perm: Perm) -> bool {
+        self.modify(id, |it| it.perm = perm)
     }
 
     pub(super) fn get(&self, idx: Idx) -> Option<&Mapping<Idx, V>> {
-        self.0
+        match self.page.get(idx) {
+            Some(page) => page.find(idx),
+            None => self.search(idx),
+        }
+    }
+
+    pub(super) fn find(&self, entry: &V) -> Option<&Mapping<Idx, V>> {
+        self.iter().find(|it| &it.entry == entry)
+    }
+
+    pub(super) fn lookup(&self, id: usize) -> Option<&Mapping<Idx, V>> {
+        self.iter().find(|it| it.id == id)
+    }
+
+    pub(super) fn iter(&self) -> impl Iterator<Item = &Mapping<Idx, V>> + '_ {
+        self.tree.values().flat_map(BTreeSet::iter)
+    }
+
+    /// Returns all mappings ordered by address, alongside their priority.
+    pub(super) fn ranked(&self) -> Vec<(usize, &Mapping<Idx, V>)> {
+        let mut ranked: Vec<_> = self
+            .tree
+            .values()
+            .rev()
+            .flat_map(BTreeSet::iter)
+            .enumerate()
+            .collect();
+        ranked.sort_by_key(|(_, it)| it.base());
+        ranked
+    }
+
+    /// Returns the winning mapping at an index, alongside the last index up to
+    /// which it remains visible and linearly decoded.
+    pub(super) fn extent(&self, idx: Idx) -> (Option<&Mapping<Idx, V>>, Idx) {
+        // Any mapping starting later may take priority
+        let next = self
+            .tree
+            .range((Excluded(idx), Unbounded))
+            .next()
+            .map_or(Idx::max_value(), |(&base, _)| base - Idx::one());
+        let Some(it) = self.get(idx) else {
+            return (None, next);
+        };
+        let last = match it.decode {
+            Decode::Full => *it.range.end(),
+            Decode::Mirror(size) => idx.saturating_add(size - Idx::one() - it.offset(idx)),
+            Decode::Mask(_) => idx,
+        };
+        (Some(it), last.min(*it.range.end()).min(next))
+    }
+
+    /// Searches for the winning mapping without consulting the page table.
+    pub(super) fn search(&self, idx: Idx) -> Option<&Mapping<Idx, V>> {
+        self.visible(idx).find(|it| it.contains(&idx))
+    }
+
+    /// Returns all mappings starting at or before the index, in order of
+    /// priority.
+    fn visible(&self, idx: Idx) -> impl Iterator<Item = &Mapping<Idx, V>> + '_ {
+        self.tree
             .range(..=idx)
             .rev()
             .flat_map(|(_, maps)| maps.iter())
-            .find(|it| it.contains(&idx))
     }
 
-    pub(super) fn find(&self, entry: &V) -> Option<&Mapping<Idx, V>> {
-        self.0
-            .iter()
-            .flat_map(|(_, maps)| maps.iter())
-            .find(|it| &it.entry == entry)
+    /// Modifies a mapping in place, updating the pages it overlaps.
+    fn modify(&mut self, id: usize, f: impl FnOnce(&mut Mapping<Idx, V>)) -> bool {
+        let Some(found) = self.lookup(id).cloned() else {
+            return false;
+        };
+        let Some(mut it) = self.remove(&found) else {
+            return false;
+        };
+        f(&mut it);
+        self.insert(it);
+        true
     }
 
-    #[allow(unused)]
-    pub(super) fn iter(&self) -> impl Iterator + '_ {
-        self.0.iter().flat_map(|(_, maps)| maps.iter())
+    /// Inserts a mapping, updating the pages it overlaps.
+    fn insert(&mut self, map: Mapping<Idx, V>) {
+        let (start, end) = (map.base(), *map.range.end());
+        self.tree.entry(map.base()).or_default().insert(map);
+        self.update(start, end);
+    }
+
+    /// Unmaps a mapping, discarding its label.
+    fn take(&mut self, map: &Mapping<Idx, V>) -> Option<V> {
+        let taken = self.remove(map)?;
+        self.tags.remove(&taken.id);
+        Some(taken.entry)
+    }
+
+    /// Removes a mapping, updating the pages it overlapped.
+    fn remove(&mut self, map: &Mapping<Idx, V>) -> Option<Mapping<Idx, V>> {
+        let maps = self.tree.get_mut(&map.base())?;
+        let taken = maps.take(map)?;
+        if maps.is_empty() {
+            self.tree.remove(&map.base());
+        }
+        self.update(taken.base(), *taken.range.end());
+        Some(taken)
+    }
+
+    /// Rebuilds all pages overlapping the range.
+    fn update(&mut self, start: Idx, end: Idx) {
+        for page in self.page.span(start, end) {
+            let data = match self.page.bounds(page) {
+                Some((start, end)) => Page::build(
+                    start,
+                    end,
+                    self.visible(end).filter(|it| *it.range.end() >= start),
+                ),
+                None => Page::Empty,
+            };
+            self.page.set(page, data);
+        }
     }
 }
 
@@ -72,18 +236,26 @@ where
     V: Entry,
 {
     fn default() -> Self {
-        Self(BTreeMap::default())
+        Self {
+            tree: BTreeMap::default(),
+            page: Table::default(),
+            tags: HashMap::default(),
+            next: 0,
+        }
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug)]
 pub(super) struct Mapping<Idx, V>
 where
     Idx: Value,
     V: Entry,
 {
+    pub(super) id: usize,
     pub(super) range: Range<Idx>,
     pub(super) entry: V,
+    pub(super) decode: Decode<Idx>,
+    pub(super) perm: Perm,
 }
 
 impl<Idx, V> Mapping<Idx, V>
@@ -91,8 +263,14 @@ where
     Idx: Value,
     V: Entry,
 {
-    fn new(range: Range<Idx>, entry: V) -> Self {
-        Self { range, entry }
+    fn new(id: usize, range: Range<Idx>, entry: V) -> Self {
+        Self {
+            id,
+            range,
+            entry,
+            decode: Decode::Full,
+            perm: Perm::default(),
+        }
     }
 
     pub(super) fn base(&self) -> Idx {
@@ -103,9 +281,40 @@ where
         *self.range.end() - *self.range.start()
     }
 
-    fn contains(&self, idx: &Idx) -> bool {
+    pub(super) fn contains(&self, idx: &Idx) -> bool {
         self.range.contains(idx)
     }
+
+    /// Decodes an index into its device-relative offset.
+    pub(super) fn offset(&self, idx: Idx) -> Idx {
+        let off = idx - self.base();
+        match self.decode {
+            Decode::Full => off,
+            Decode::Mirror(size) => off % size,
+            Decode::Mask(mask) => off & mask,
+        }
+    }
+}
+
+/// Compares mappings by identity.
+///
+/// As mappings are uniquely identified, this is consistent with their
+/// [ordering](Ord).
+impl<Idx, V> PartialEq for Mapping<Idx, V>
+where
+    Idx: Value,
+    V: Entry,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<Idx, V> Eq for Mapping<Idx, V>
+where
+    Idx: Value,
+    V: Entry,
+{
 }
 
 impl<Idx, V> Ord for Mapping<Idx, V>
@@ -116,7 +325,10 @@ where
     fn cmp(&self, other: &Self) -> Ordering {
         match self.base().cmp(&other.base()) {
             ord @ (Ordering::Less | Ordering::Greater) => ord,
-            Ordering::Equal => self.len().cmp(&other.len()),
+            Ordering::Equal => match self.len().cmp(&other.len()) {
+                ord @ (Ordering::Less | Ordering::Greater) => ord,
+                Ordering::Equal => self.id.cmp(&other.id),
+            },
         }
     }
 }
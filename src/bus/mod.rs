@@ -11,28 +11,55 @@
 //!
 //! [memory-mapped I/O]: https://en.wikipedia.org/wiki/Memory-mapped_I/O
 
+use std::cell::{Cell, RefCell};
 use std::fmt::Debug;
 use std::ops::RangeInclusive;
 
 use thiserror::Error;
 
-use self::map::Map;
-use crate::arch::{Address, TryAddress, Value};
+use self::map::{Map, Mapping};
+use self::trace::{Action, Event, Tracer, Watch};
+use crate::arch::{Access, Address, Context, TryAddress, Value};
 use crate::blk::Block;
-use crate::dev::{Device, Dynamic};
+use crate::dev::{self, Device, Dynamic};
+#[cfg(feature = "state")]
+use crate::state;
 
 mod map;
 mod mux;
+mod open;
+mod page;
+mod perm;
 
 pub mod adapt;
+pub mod render;
+pub mod trace;
 
-pub use self::mux::Mux;
+pub use self::mux::{Handle, Mux, Region};
+pub use self::open::OpenBus;
+pub use self::perm::Perm;
 
 /// Mappable address range.
 pub(crate) type Range<Idx> = RangeInclusive<Idx>;
 
 /// Address [bus][bus].
 ///
+/// # Open bus
+///
+/// By default, accessing an unmapped index through [`Address`] panics. This
+/// can be changed by configuring the bus's [`OpenBus`] behaviour.
+///
+/// # Protection
+///
+/// Each mapping carries a set of access [permissions](Perm), which are checked
+/// on every access. Accesses lacking permission result in an
+/// [`Error::Protected`] fault.
+///
+/// # Tracing
+///
+/// Accesses can be observed by registering [watchpoints](trace) over ranges of
+/// the bus.
+///
 /// [bus]: https://en.wikipedia.org/wiki/Bus_(computing)
 #[derive(Debug, Default)]
 pub struct Bus<Idx, V>
@@ -41,6 +68,9 @@ where
     V: Value,
 {
     maps: Map<Idx, Dynamic<Idx, V>>,
+    open: OpenBus<Idx, V>,
+    last: Cell<V>,
+    trace: Option<Box<RefCell<Tracer<Idx, V>>>>,
 }
 
 impl<Idx, V> Bus<Idx, V>
@@ -54,10 +84,453 @@ where
         Self::default()
     }
 
+    /// Constructs a new, empty `Bus` whose page table resolves addresses in
+    /// pages of `2^bits` indices.
+    ///
+    /// # Note
+    ///
+    /// Lookups within a page containing only a single mapping are resolved
+    /// directly, whereas pages split between several mappings must be
+    /// searched linearly. Finer granularity speeds up lookups around small
+    /// devices at the cost of memory.
+    ///
+    /// Only the first 2<sup>16</sup> pages are cached; indices beyond these
+    /// are resolved by searching the memory map in logarithmic time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if pages of `2^bits` indices cannot be represented.
+    #[must_use]
+    pub fn with_granularity(bits: u32) -> Self {
+        Self {
+            maps: Map::with_granularity(bits),
+            ..Default::default()
+        }
+    }
+
+    /// Gets the bus's open-bus behaviour.
+    #[must_use]
+    pub fn open_bus(&self) -> &OpenBus<Idx, V> {
+        &self.open
+    }
+
+    /// Sets the bus's open-bus behaviour.
+    pub fn set_open_bus(&mut self, open: OpenBus<Idx, V>) {
+        self.open = open;
+    }
+
+    /// Maps a device to the provided range, mirrored every `size` indices.
+    ///
+    /// The device is visible throughout the range, with each index offset
+    /// modulo `size` from the start of the range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is not positive.
+    pub fn map_mirror(&mut self, range: Range<Idx>, size: Idx, dev: Dynamic<Idx, V>) -> Handle {
+        assert!(size > Idx::zero(), "mirror size must be positive: {size:?}");
+        Handle(self.maps.map_with(range, dev, Decode::Mirror(size)))
+    }
+
+    /// Maps a device to the provided range, decoding only the address lines
+    /// set within `mask`.
+    ///
+    /// Address lines cleared within `mask` are treated as "don't care" bits,
+    /// and are ignored when offsetting from the start of the range.
+    pub fn map_masked(&mut self, range: Range<Idx>, mask: Idx, dev: Dynamic<Idx, V>) -> Handle {
+        Handle(self.maps.map_with(range, dev, Decode::Mask(mask)))
+    }
+
+    /// Sets the access permissions of a mapping.
+    ///
+    /// Returns `false` if the mapping does not exist.
+    pub fn protect(&mut self, handle: Handle, perm: Perm) -> bool {
+        self.maps.protect(handle.0, perm)
+    }
+
+    /// Fetches from the specified address.
+    ///
+    /// Behaves as [`Address::read`], but requires [execute](Perm::X) rather
+    /// than read permission.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the fetch is not successful, unless the index is unmapped and
+    /// handled by the open-bus behaviour.
+    #[must_use]
+    pub fn fetch(&self, index: Idx) -> V {
+        self.recover(self.try_fetch(index), "`Bus::fetch`")
+    }
+
+    /// Fallibly fetches from the specified address.
+    ///
+    /// Behaves as [`TryAddress::try_read`], but requires [execute](Perm::X)
+    /// rather than read permission. Fetches are forwarded to nested buses,
+    /// which check their own permissions in turn.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the fetch is not successful.
+    pub fn try_fetch(&self, index: Idx) -> Result<V, Error<Idx>> {
+        let ctx = Context {
+            access: Access::Fetch,
+            ..Context::default()
+        };
+        self.load(index, ctx)
+    }
+
+    /// Fallibly reads a contiguous block starting at the specified address.
+    ///
+    /// Behaves as [`Device::try_read_block`], splitting the block across
+    /// mappings. Unmapped indices are handled by the bus's
+    /// [open-bus](OpenBus) behaviour.
+    ///
+    /// # Errors
+    ///
+    /// Errors if any index within the block is unmapped or lacks read
+    /// permission, in which case no reads are performed. Errors if a mapped
+    /// device fails to read its portion of the block, in which case the
+    /// contents of `buf` are unspecified.
+    pub fn try_read_block(&self, index: Idx, buf: &mut [V]) -> Result<(), Error<Idx>> {
+        self.check(index, buf.len(), Perm::R)?;
+        self.split(index, buf.len(), |idx, span, it| {
+            let run = &mut buf[span];
+            if let Some(it) = it {
+                let offset = it.offset(idx);
+                it.entry
+                    .try_read_block(offset, run)
+                    .map_err(|source| Error::Device {
+                        index: idx,
+                        offset,
+                        source,
+                    })?;
+                self.emit_block(idx, Access::Read, run);
+            } else {
+                let mut idx = idx;
+                for value in run.iter_mut() {
+                    *value = self.recover(Err(Error::Unmapped(idx)), "`Bus::try_read_block`");
+                    idx = idx.wrapping_add(&Idx::one());
+                }
+            }
+            if let Some(&value) = run.last() {
+                self.last.set(value);
+            }
+            Ok(())
+        })
+    }
+
+    /// Fallibly writes a contiguous block starting at the specified address.
+    ///
+    /// Behaves as [`Device::try_write_block`], splitting the block across
+    /// mappings. Unmapped indices are handled by the bus's
+    /// [open-bus](OpenBus) behaviour.
+    ///
+    /// # Errors
+    ///
+    /// Errors if any index within the block is unmapped or lacks write
+    /// permission, in which case no writes are performed. Errors if a mapped
+    /// device fails to write its portion of the block, in which case the
+    /// preceding portions will have already been written.
+    pub fn try_write_block(&mut self, index: Idx, buf: &[V]) -> Result<(), Error<Idx>> {
+        self.check(index, buf.len(), Perm::W)?;
+        self.split(index, buf.len(), |idx, span, it| {
+            let run = &buf[span];
+            if let Some(it) = it {
+                let offset = it.offset(idx);
+                it.entry
+                    .borrow_mut()
+                    .try_write_block(offset, run)
+                    .map_err(|source| Error::Device {
+                        index: idx,
+                        offset,
+                        source,
+                    })?;
+                self.emit_block(idx, Access::Write, run);
+            }
+            if let Some(&value) = run.last() {
+                self.last.set(value);
+            }
+            Ok(())
+        })
+    }
+
+    /// Registers a watchpoint over the provided range.
+    ///
+    /// The watchpoint's action is performed upon each successful access of the
+    /// specified kind, while tracing is [enabled](Self::set_tracing).
+    pub fn watch(&mut self, range: Range<Idx>, access: Access, action: Action<Idx, V>) -> Watch {
+        self.trace
+            .get_or_insert_with(Box::default)
+            .get_mut()
+            .watch(range, access, action)
+    }
+
+    /// Removes a watchpoint.
+    ///
+    /// Returns `false` if the watchpoint does not exist.
+    pub fn unwatch(&mut self, watch: Watch) -> bool {
+        self.trace
+            .as_mut()
+            .is_some_and(|trace| trace.get_mut().unwatch(watch))
+    }
+
+    /// Checks if tracing is enabled.
+    #[must_use]
+    pub fn tracing(&self) -> bool {
+        self.trace
+            .as_ref()
+            .is_some_and(|trace| trace.borrow().enabled())
+    }
+
+    /// Enables or disables tracing.
+    pub fn set_tracing(&mut self, enable: bool) {
+        self.trace
+            .get_or_insert_with(Box::default)
+            .get_mut()
+            .enable(enable);
+    }
+
+    /// Takes all events recorded by logging watchpoints.
+    pub fn take_log(&mut self) -> Vec<Event<Idx, V>> {
+        self.trace
+            .as_mut()
+            .map(|trace| trace.get_mut().take())
+            .unwrap_or_default()
+    }
+
+    /// Returns an iterator over all mapped regions, ordered by address.
+    pub fn iter(&self) -> impl Iterator<Item = Region<Idx, V>> + '_ {
+        self.maps.ranked().into_iter().map(|(prio, it)| Region {
+            handle: Handle(it.id),
+            label: self.maps.label_of(it.id).cloned(),
+            decode: it.decode,
+            perm: it.perm,
+            range: it.range.clone(),
+            dev: it.entry.clone(),
+            prio,
+        })
+    }
+
     /// Clears the bus, removing all devices.
     pub fn clear(&mut self) {
         self.maps.clear();
     }
+
+    /// Returns all mapped devices, ordered by address.
+    ///
+    /// Devices mapped more than once appear only at their first mapping.
+    #[cfg(feature = "state")]
+    fn devices(&self) -> Vec<&Dynamic<Idx, V>> {
+        let mut devs: Vec<&Dynamic<Idx, V>> = Vec::new();
+        for it in self.maps.iter() {
+            if !devs.contains(&&it.entry) {
+                devs.push(&it.entry);
+            }
+        }
+        devs
+    }
+
+    /// Resolves the mapping at an index, checking it grants `perm`.
+    fn resolve(
+        &self,
+        index: Idx,
+        perm: Perm,
+    ) -> Result<&Mapping<Idx, Dynamic<Idx, V>>, Error<Idx>> {
+        let it = self.maps.get(index).ok_or(Error::Unmapped(index))?;
+        if it.perm.contains(perm) {
+            Ok(it)
+        } else {
+            Err(Error::Protected { index, perm })
+        }
+    }
+
+    /// Loads from the mapping at an index within the provided context.
+    ///
+    /// Fetches require execute permission, whereas all other loads require
+    /// read permission. The kind of access is preserved, unless it is a write.
+    fn load(&self, index: Idx, ctx: Context) -> Result<V, Error<Idx>> {
+        let ctx = match ctx.access {
+            Access::Write => Context {
+                access: Access::Read,
+                ..ctx
+            },
+            _ => ctx,
+        };
+        let perm = match ctx.access {
+            Access::Fetch => Perm::X,
+            _ => Perm::R,
+        };
+        let it = self.resolve(index, perm)?;
+        let offset = it.offset(index);
+        let value = it
+            .entry
+            .try_read_ctx(offset, ctx)
+            .map_err(|source| Error::Device {
+                index,
+                offset,
+                source,
+            })?;
+        self.last.set(value);
+        self.emit(index, ctx, value);
+        Ok(value)
+    }
+
+    /// Stores to the mapping at an index within the provided context.
+    ///
+    /// The kind of access is preserved, unless it is a read or fetch.
+    fn store(&self, index: Idx, value: V, ctx: Context) -> Result<(), Error<Idx>> {
+        let ctx = match ctx.access {
+            Access::Read | Access::Fetch => Context {
+                access: Access::Write,
+                ..ctx
+            },
+            _ => ctx,
+        };
+        let it = self.resolve(index, Perm::W)?;
+        let offset = it.offset(index);
+        it.entry
+            .borrow_mut()
+            .try_write_ctx(offset, value, ctx)
+            .map_err(|source| Error::Device {
+                index,
+                offset,
+                source,
+            })?;
+        self.last.set(value);
+        self.emit(index, ctx, value);
+        Ok(())
+    }
+
+    /// Inspects the mapping at an index on behalf of a debugger.
+    ///
+    /// Unlike [`Bus::load`], this bypasses protection, and neither drives the
+    /// bus nor triggers watchpoints.
+    fn inspect(&self, index: Idx, ctx: Context) -> Result<V, Error<Idx>> {
+        let it = self.maps.get(index).ok_or(Error::Unmapped(index))?;
+        let offset = it.offset(index);
+        it.entry
+            .try_read_ctx(offset, ctx)
+            .map_err(|source| Error::Device {
+                index,
+                offset,
+                source,
+            })
+    }
+
+    /// Patches the mapping at an index on behalf of a debugger.
+    ///
+    /// Unlike [`Bus::store`], this bypasses protection, and neither drives the
+    /// bus nor triggers watchpoints.
+    fn patch(&self, index: Idx, value: V, ctx: Context) -> Result<(), Error<Idx>> {
+        let it = self.maps.get(index).ok_or(Error::Unmapped(index))?;
+        let offset = it.offset(index);
+        it.entry
+            .borrow_mut()
+            .try_write_ctx(offset, value, ctx)
+            .map_err(|source| Error::Device {
+                index,
+                offset,
+                source,
+            })
+    }
+
+    /// Emits an event to the tracer, if any.
+    fn emit(&self, index: Idx, ctx: Context, value: V) {
+        if let Some(trace) = &self.trace {
+            let event = Event { index, ctx, value };
+            // Release the tracer before invoking callbacks, as they may access
+            // the bus themselves
+            let calls = trace.borrow_mut().fire(event);
+            for call in calls {
+                // Callbacks are not re-entered by their own accesses
+                if let Ok(mut f) = call.try_borrow_mut() {
+                    f(&event);
+                }
+            }
+        }
+    }
+
+    /// Splits a block into runs of indices resolved by the same mapping.
+    ///
+    /// Calls `f` with the starting index, the run's span within the block,
+    /// and its mapping, if any, stopping at the first error.
+    fn split(
+        &self,
+        index: Idx,
+        len: usize,
+        mut f: impl FnMut(
+            Idx,
+            std::ops::Range<usize>,
+            Option<&Mapping<Idx, Dynamic<Idx, V>>>,
+        ) -> Result<(), Error<Idx>>,
+    ) -> Result<(), Error<Idx>> {
+        let mut idx = index;
+        let mut done = 0;
+        while done < len {
+            let (it, last) = self.maps.extent(idx);
+            let run = (last - idx)
+                .to_usize()
+                .map_or(len - done, |n| (len - done).min(n.saturating_add(1)));
+            f(idx, done..done + run, it)?;
+            idx = last.wrapping_add(&Idx::one());
+            done += run;
+        }
+        Ok(())
+    }
+
+    /// Checks that every index of a block is accessible with `perm`.
+    ///
+    /// Unmapped indices are only accessible when handled by the open-bus
+    /// behaviour.
+    fn check(&self, index: Idx, len: usize, perm: Perm) -> Result<(), Error<Idx>> {
+        self.split(index, len, |idx, _, it| match it {
+            Some(it) if !it.perm.contains(perm) => Err(Error::Protected { index: idx, perm }),
+            None if !self.open.recovers() => Err(Error::Unmapped(idx)),
+            _ => Ok(()),
+        })
+    }
+
+    /// Emits an event to the tracer for each element of a block.
+    fn emit_block(&self, index: Idx, access: Access, buf: &[V]) {
+        if self.trace.is_some() {
+            let ctx = Context {
+                access,
+                ..Context::default()
+            };
+            let mut index = index;
+            for &value in buf {
+                self.emit(index, ctx, value);
+                index = index.wrapping_add(&Idx::one());
+            }
+        }
+    }
+
+    /// Resolves an unmapped load using the open-bus behaviour.
+    fn open_load(&self, res: Result<V, Error<Idx>>) -> Result<V, Error<Idx>> {
+        match res {
+            Err(Error::Unmapped(index)) if self.open.recovers() => {
+                Ok(self.open.read(index, self.last.get()).unwrap())
+            }
+            res => res,
+        }
+    }
+
+    /// Resolves an unmapped store using the open-bus behaviour.
+    fn open_store(&self, res: Result<(), Error<Idx>>, value: V) -> Result<(), Error<Idx>> {
+        match res {
+            Err(Error::Unmapped(_)) if self.open.recovers() => {
+                self.last.set(value);
+                Ok(())
+            }
+            res => res,
+        }
+    }
+
+    /// Recovers from a failed load using the open-bus behaviour.
+    fn recover(&self, res: Result<V, Error<Idx>>, what: &str) -> V {
+        self.open_load(res)
+            .unwrap_or_else(|err| panic!("{what}: {err}"))
+    }
 }
 
 impl<Idx, V> Address<Idx, V> for Bus<Idx, V>
@@ -66,11 +539,14 @@ where
     V: Value,
 {
     fn read(&self, index: Idx) -> V {
-        self.try_read(index).unwrap()
+        self.recover(self.try_read(index), "`<Bus as Address>::read`")
     }
 
     fn write(&mut self, index: Idx, value: V) {
-        self.try_write(index, value).unwrap();
+        let res = self.try_write(index, value);
+        if let Err(err) = self.open_store(res, value) {
+            panic!("`<Bus as Address>::write`: {err}");
+        }
     }
 }
 
@@ -82,17 +558,15 @@ where
     type Error = Error<Idx>;
 
     fn try_read(&self, index: Idx) -> Result<V, Self::Error> {
-        self.maps
-            .get(index)
-            .ok_or(Error::Unmapped(index))
-            .map(|it| it.entry.read(index - it.base()))
+        self.load(index, Context::default())
     }
 
     fn try_write(&mut self, index: Idx, value: V) -> Result<(), Self::Error> {
-        self.maps
-            .get(index)
-            .ok_or(Error::Unmapped(index))
-            .map(|it| it.entry.borrow_mut().write(index - it.base(), value))
+        let ctx = Context {
+            access: Access::Write,
+            ..Context::default()
+        };
+        self.store(index, value, ctx)
     }
 }
 
@@ -101,6 +575,30 @@ where
     Idx: Value,
     V: Value,
 {
+    #[cfg(feature = "state")]
+    fn save_state(&self, state: &mut state::Writer) {
+        state.section("bus", 1, |state| {
+            state.value(self.last.get());
+            let devs = self.devices();
+            state.count(devs.len());
+            for dev in devs {
+                dev.save_state(state);
+            }
+        });
+    }
+
+    #[cfg(feature = "state")]
+    fn load_state(&mut self, state: &mut state::Reader) -> Result<(), state::Error> {
+        state.section("bus", 1, |state, _| {
+            self.last.set(state.value()?);
+            let devs = self.devices();
+            state::expect(devs.len(), state.count()?)?;
+            for dev in devs {
+                dev.clone().load_state(state)?;
+            }
+            Ok(())
+        })
+    }
 }
 
 impl<Idx, V> Device<Idx, V> for Bus<Idx, V>
@@ -108,6 +606,135 @@ where
     Idx: Value,
     V: Value,
 {
+    /// Fallibly reads from the specified address.
+    ///
+    /// Unlike [`TryAddress::try_read`], unmapped indices are handled by the
+    /// bus's [open-bus](OpenBus) behaviour, such that a nested bus behaves as
+    /// it would through [`Address`].
+    fn try_read_dyn(&self, index: Idx) -> Result<V, dev::Error> {
+        self.try_read_ctx(index, Context::default())
+    }
+
+    /// Fallibly writes to the specified address.
+    ///
+    /// Unlike [`TryAddress::try_write`], unmapped indices are handled by the
+    /// bus's [open-bus](OpenBus) behaviour, such that a nested bus behaves as
+    /// it would through [`Address`].
+    fn try_write_dyn(&mut self, index: Idx, value: V) -> Result<(), dev::Error> {
+        let ctx = Context {
+            access: Access::Write,
+            ..Context::default()
+        };
+        self.try_write_ctx(index, value, ctx)
+    }
+
+    /// Fallibly reads from the specified address within the provided context.
+    ///
+    /// The context is forwarded to the mapped device. [Debug](Access::Debug)
+    /// reads bypass protection, and neither drive the bus nor trigger
+    /// watchpoints. Unmapped indices are handled by the bus's
+    /// [open-bus](OpenBus) behaviour.
+    fn try_read_ctx(&self, index: Idx, ctx: Context) -> Result<V, dev::Error> {
+        let res = match ctx.access {
+            Access::Debug => self.inspect(index, ctx),
+            _ => self.load(index, ctx),
+        };
+        self.open_load(res).map_err(Into::into)
+    }
+
+    /// Fallibly writes to the specified address within the provided context.
+    ///
+    /// The context is forwarded to the mapped device. [Debug](Access::Debug)
+    /// writes bypass protection, and neither drive the bus nor trigger
+    /// watchpoints. Unmapped indices are handled by the bus's
+    /// [open-bus](OpenBus) behaviour.
+    fn try_write_ctx(&mut self, index: Idx, value: V, ctx: Context) -> Result<(), dev::Error> {
+        let res = if ctx.access == Access::Debug {
+            match self.patch(index, value, ctx) {
+                Err(Error::Unmapped(_)) if self.open.recovers() => Ok(()),
+                res => res,
+            }
+        } else {
+            let res = self.store(index, value, ctx);
+            self.open_store(res, value)
+        };
+        res.map_err(Into::into)
+    }
+
+    fn try_read_block(&self, index: Idx, buf: &mut [V]) -> Result<(), dev::Error> {
+        Bus::try_read_block(self, index, buf).map_err(Into::into)
+    }
+
+    fn try_write_block(&mut self, index: Idx, buf: &[V]) -> Result<(), dev::Error> {
+        Bus::try_write_block(self, index, buf).map_err(Into::into)
+    }
+
+    fn read_block(&self, index: Idx, buf: &mut [V]) {
+        self.try_read_block(index, buf)
+            .unwrap_or_else(|err| panic!("`<Bus as Device>::read_block`: {err}"));
+    }
+
+    fn write_block(&mut self, index: Idx, buf: &[V]) {
+        self.try_write_block(index, buf)
+            .unwrap_or_else(|err| panic!("`<Bus as Device>::write_block`: {err}"));
+    }
+
+    /// Aggregates the dirty ranges of all mapped devices, translated into the
+    /// bus's address space.
+    ///
+    /// # Note
+    ///
+    /// Ranges within masked mappings cannot be precisely translated, and so
+    /// their entire mapping is reported as dirty.
+    fn dirty(&self) -> Vec<RangeInclusive<usize>> {
+        let mut ranges = Vec::new();
+        for it in self.maps.iter() {
+            let (Some(base), Some(end)) = (it.base().to_usize(), it.range.end().to_usize()) else {
+                continue;
+            };
+            let dirty = it.entry.dirty();
+            // Determine where each device offset appears within the mapping
+            let bases: Vec<usize> = match it.decode {
+                Decode::Full => vec![base],
+                Decode::Mirror(size) => {
+                    let size = size.to_usize().unwrap_or(usize::MAX);
+                    std::iter::successors(Some(base), |at| at.checked_add(size))
+                        .take_while(|&at| at <= end)
+                        .collect()
+                }
+                Decode::Mask(_) => {
+                    if !dirty.is_empty() {
+                        ranges.push(base..=end);
+                    }
+                    continue;
+                }
+            };
+            for at in bases {
+                ranges.extend(
+                    dirty
+                        .iter()
+                        .map(|off| at.saturating_add(*off.start())..=at.saturating_add(*off.end()))
+                        .filter(|range| *range.start() <= end)
+                        .map(|range| *range.start()..=*range.end().min(&end)),
+                );
+            }
+        }
+        coalesce(ranges)
+    }
+
+    fn clean(&mut self) {
+        for it in self.maps.iter() {
+            it.entry.clone().clean();
+        }
+    }
+
+    fn peek(&self, index: Idx) -> V {
+        self.read_ctx(index, Context::new(Access::Debug, 0))
+    }
+
+    fn poke(&mut self, index: Idx, value: V) {
+        self.write_ctx(index, value, Context::new(Access::Debug, 0));
+    }
 }
 
 impl<Idx, V, const N: usize> From<[(Range<Idx>, Dynamic<Idx, V>); N]> for Bus<Idx, V>
@@ -133,20 +760,122 @@ where
         self.maps.get(index).map(|map| &map.entry).cloned()
     }
 
-    fn map(&mut self, range: Range<Idx>, dev: Dynamic<Idx, V>) {
-        self.maps.map(range, dev);
+    fn map(&mut self, range: Range<Idx>, dev: Dynamic<Idx, V>) -> Handle {
+        Handle(self.maps.map(range, dev))
     }
 
     fn unmap(&mut self, dev: &Dynamic<Idx, V>) -> Option<Dynamic<Idx, V>> {
         self.maps.unmap(dev)
     }
+
+    fn unmap_handle(&mut self, handle: Handle) -> Option<Dynamic<Idx, V>> {
+        self.maps.unmap_id(handle.0)
+    }
+
+    fn unmap_range(&mut self, range: Range<Idx>) -> Vec<Dynamic<Idx, V>> {
+        self.maps.unmap_range(&range)
+    }
+
+    fn remap(&mut self, handle: Handle, range: Range<Idx>) -> bool {
+        self.maps.remap(handle.0, range)
+    }
+
+    fn label(&mut self, handle: Handle, label: Option<String>) -> bool {
+        self.maps.label(handle.0, label)
+    }
+
+    fn regions(&self) -> Vec<Region<Idx, V>> {
+        self.iter().collect()
+    }
+}
+
+/// Sorts and merges overlapping or adjacent ranges.
+pub(crate) fn coalesce(mut ranges: Vec<RangeInclusive<usize>>) -> Vec<RangeInclusive<usize>> {
+    ranges.sort_unstable_by_key(|range| *range.start());
+    let mut merged: Vec<RangeInclusive<usize>> = Vec::new();
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if *range.start() <= last.end().saturating_add(1) => {
+                *last = *last.start()..=*last.end().max(range.end());
+            }
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Address decoding of a mapping.
+///
+/// Determines how indices within a mapped range are translated into offsets of
+/// the mapped device.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Decode<Idx: Value> {
+    /// All address lines are decoded.
+    #[default]
+    Full,
+    /// Offsets repeat every provided number of indices.
+    Mirror(Idx),
+    /// Only address lines set within the mask are decoded.
+    Mask(Idx),
 }
 
 /// A type specifying general categories of [`Bus`] error.
-#[derive(Debug, Error, PartialEq)]
+#[derive(Debug, Error)]
 pub enum Error<Idx: Value> {
     #[error("index is not mapped: {0:?}")]
     Unmapped(Idx),
+    #[error("protection fault at index {index:?} (requires {perm})")]
+    Protected {
+        /// Faulting index on the bus.
+        index: Idx,
+        /// Permission required by the access.
+        perm: Perm,
+    },
+    #[error("device error at index {index:?} (offset {offset:?})")]
+    Device {
+        /// Faulting index on the bus.
+        index: Idx,
+        /// Device-relative offset of the faulting index.
+        offset: Idx,
+        /// Underlying device error.
+        source: dev::Error,
+    },
+}
+
+/// Compares errors by value.
+///
+/// As device errors are type-erased, their sources cannot be compared. Device
+/// errors are instead considered equal if they occur at the same index and
+/// offset.
+impl<Idx: Value> PartialEq for Error<Idx> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Unmapped(lhs), Self::Unmapped(rhs)) => lhs == rhs,
+            (
+                Self::Protected {
+                    index: li,
+                    perm: lp,
+                },
+                Self::Protected {
+                    index: ri,
+                    perm: rp,
+                },
+            ) => li == ri && lp == rp,
+            (
+                Self::Device {
+                    index: li,
+                    offset: lo,
+                    ..
+                },
+                Self::Device {
+                    index: ri,
+                    offset: ro,
+                    ..
+                },
+            ) => li == ri && lo == ro,
+            _ => false,
+        }
+    }
 }
 
 #[allow(clippy::items_after_statements)]
@@ -154,7 +883,7 @@ pub enum Error<Idx: Value> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::mem::Ram;
+    use crate::mem::{Ram, Rom};
 
     fn setup() -> Bus<usize, u8> {
         Bus::from([
@@ -205,6 +934,527 @@ mod tests {
         assert_eq!(bus.get(0x000), None);
     }
 
+    #[test]
+    fn unmap_handle_works() {
+        let mut bus = Bus::<usize, u8>::new();
+        let dev = Ram::from(&[0; 0x100]).to_dynamic();
+        // Mirror the same device into several ranges
+        let lo = bus.map(0x000..=0x0ff, dev.clone());
+        let hi = bus.map(0x100..=0x1ff, dev.clone());
+        assert_ne!(lo, hi);
+        assert_eq!(bus.unmap_handle(hi), Some(dev.clone()));
+        assert_eq!(bus.unmap_handle(hi), None);
+        assert_eq!(bus.get(0x100), None);
+        assert_eq!(bus.get(0x000), Some(dev));
+    }
+
+    #[test]
+    fn unmap_range_works() {
+        let mut bus = setup();
+        let boot = Ram::from(&[0xbb; 0x80]).to_dynamic();
+        bus.map(0x000..=0x07f, boot.clone());
+        assert_eq!(bus.read(0x000), 0xbb);
+        // Only devices contained within the range are unmapped
+        assert_eq!(bus.unmap_range(0x000..=0x0ff).len(), 2);
+        assert_eq!(bus.get(0x000), None);
+        assert_eq!(bus.read(0x100), 1);
+        assert!(bus.unmap_range(0x180..=0x27f).is_empty());
+    }
+
+    #[test]
+    fn remap_works() {
+        let mut bus = setup();
+        let dev = Ram::from(&[3; 0x100]).to_dynamic();
+        let handle = bus.map(0x300..=0x3ff, dev.clone());
+        assert!(bus.remap(handle, 0x400..=0x4ff));
+        assert_eq!(bus.get(0x300), None);
+        assert_eq!(bus.get(0x400), Some(dev.clone()));
+        // Handles remain valid after remapping
+        assert_eq!(bus.unmap_handle(handle), Some(dev));
+        assert!(!bus.remap(handle, 0x300..=0x3ff));
+    }
+
+    #[test]
+    fn label_works() {
+        let mut bus = Bus::<usize, u8>::new();
+        let handle = bus.map(0x000..=0x0ff, Ram::from(&[0; 0x100]).to_dynamic());
+        assert!(bus.label(handle, Some(String::from("wram"))));
+        let regions = bus.regions();
+        assert_eq!(regions[0].handle, handle);
+        assert_eq!(regions[0].label.as_deref(), Some("wram"));
+        assert_eq!(bus.read(0x000), 0);
+        // Labels are kept when remapping
+        assert!(bus.remap(handle, 0x100..=0x1ff));
+        assert_eq!(bus.regions()[0].label.as_deref(), Some("wram"));
+        // Labels can be provided when mapping
+        let rom = Rom::from(&[1; 0x100]).to_dynamic();
+        let handle = bus.map_labeled(0x200..=0x2ff, rom.clone(), String::from("rom"));
+        assert_eq!(bus.regions()[1].label.as_deref(), Some("rom"));
+        assert!(bus.label(handle, None));
+        assert_eq!(bus.regions()[1].label, None);
+        assert_eq!(bus.unmap_handle(handle), Some(rom));
+        assert!(!bus.label(handle, Some(String::from("gone"))));
+    }
+
+    #[allow(clippy::large_stack_arrays)]
+    #[test]
+    fn map_mirror_works() {
+        // Model the NES CPU memory map:
+        //
+        // | Range           | Size  | Device                     |
+        // |-----------------|-------|----------------------------|
+        // | `$0000..=$07FF` | 2 KiB | Internal RAM               |
+        // | `$0800..=$1FFF` | 6 KiB | Mirrors of `$0000..=$07FF` |
+        // | `$2000..=$2007` | 8 B   | PPU registers              |
+        // | `$2008..=$3FFF` | 8 KiB | Mirrors of `$2000..=$2007` |
+        let mut bus = Bus::<u16, u8>::new();
+        let wram = Ram::from(&[0; 0x800]).to_dynamic();
+        let ppu = Ram::from(&[0; 0x008]).to_dynamic();
+        bus.map_mirror(0x0000..=0x1fff, 0x800, wram.clone());
+        bus.map_mirror(0x2000..=0x3fff, 0x008, ppu.clone());
+        // Writes to any mirror are visible in all others
+        bus.write(0x0801, 0xaa);
+        for index in [0x0001, 0x0801, 0x1001, 0x1801] {
+            assert_eq!(bus.read(index), 0xaa);
+        }
+        assert_eq!(wram.read(0x0001), 0xaa);
+        bus.write(0x3ffe, 0xbb);
+        for index in (0x2006..=0x3ffe).step_by(8) {
+            assert_eq!(bus.read(index), 0xbb);
+        }
+        assert_eq!(ppu.read(0x0006), 0xbb);
+        // Mirroring is reported within the memory map
+        assert_eq!(bus.regions()[0].decode, Decode::Mirror(0x800));
+    }
+
+    #[test]
+    fn map_masked_works() {
+        let mut bus = Bus::<u16, u8>::new();
+        let reg = Ram::from(&[0; 0x004]).to_dynamic();
+        // Only decode A0, A1, ignoring all other address lines
+        bus.map_masked(0x4000..=0x4fff, 0x0003, reg.clone());
+        bus.write(0x4002, 0xcc);
+        assert_eq!(bus.read(0x4ffe), 0xcc);
+        assert_eq!(bus.read(0x4a46), 0xcc);
+        assert_eq!(reg.read(0x0002), 0xcc);
+        // Don't-care bits need not be contiguous
+        let mut bus = Bus::<u16, u8>::new();
+        let reg = Ram::from(&[0; 0x020]).to_dynamic();
+        bus.map_masked(0x0000..=0x00ff, 0x0011, reg.clone());
+        bus.write(0x0010, 0xdd);
+        assert_eq!(bus.read(0x00f2), 0xdd);
+        assert_eq!(bus.read(0x00be), 0xdd);
+        assert_eq!(bus.read(0x0003), 0x00);
+        assert_eq!(reg.read(0x0010), 0xdd);
+    }
+
+    #[test]
+    fn protect_works() {
+        let mut bus = setup();
+        let rom = bus.regions()[0].handle;
+        let ram = bus.regions()[1].handle;
+        // Write-protect the first region
+        assert!(bus.protect(rom, Perm::RX));
+        assert_eq!(bus.try_read(0x000), Ok(0));
+        assert_eq!(
+            bus.try_write(0x042, 0xaa),
+            Err(Error::Protected {
+                index: 0x042,
+                perm: Perm::W,
+            })
+        );
+        assert_eq!(bus.read(0x042), 0);
+        assert_eq!(bus.regions()[0].perm, Perm::RX);
+        // Execute-never the second region
+        assert!(bus.protect(ram, Perm::RW));
+        assert_eq!(bus.try_fetch(0x080), Ok(0));
+        assert_eq!(
+            bus.try_fetch(0x180),
+            Err(Error::Protected {
+                index: 0x180,
+                perm: Perm::X,
+            })
+        );
+        bus.write(0x180, 0xbb);
+        assert_eq!(bus.read(0x180), 0xbb);
+    }
+
+    #[test]
+    fn protect_nested_works() {
+        let mut inner = Bus::<usize, u8>::new();
+        let ram = inner.map(0x00..=0xff, Ram::from(&[1; 0x100]).to_dynamic());
+        inner.protect(ram, Perm::RW);
+        let mut bus = Bus::new();
+        bus.map(0x100..=0x1ff, inner.to_dynamic());
+        // Fetches are checked by the nested bus
+        assert_eq!(bus.try_read(0x100).ok(), Some(1));
+        assert!(matches!(
+            bus.try_fetch(0x100),
+            Err(Error::Device { index: 0x100, .. })
+        ));
+    }
+
+    #[test]
+    fn probe_works() {
+        let mut bus = setup();
+        bus.set_open_bus(OpenBus::Latch);
+        bus.watch(0x000..=0x2ff, Access::Read, Action::Log);
+        bus.set_tracing(true);
+        // Peeks neither drive the bus nor trigger watchpoints
+        assert_eq!(bus.read(0x100), 1);
+        assert_eq!(bus.peek(0x200), 2);
+        assert_eq!(bus.peek(0x300), 1);
+        assert_eq!(bus.take_log().len(), 1);
+        // Pokes bypass protection
+        let rom = bus.regions()[0].handle;
+        bus.protect(rom, Perm::R);
+        bus.poke(0x042, 0xaa);
+        assert_eq!(bus.read(0x042), 0xaa);
+    }
+
+    #[test]
+    fn contextual_works() {
+        // Fetch-only device, yielding the initiator of each fetch
+        #[derive(Debug)]
+        struct Rom;
+        impl Address<usize, u8> for Rom {
+            fn read(&self, _: usize) -> u8 {
+                0xff
+            }
+            fn write(&mut self, _: usize, _: u8) {}
+        }
+        impl Block for Rom {}
+        impl Device<usize, u8> for Rom {
+            fn try_read_ctx(&self, index: usize, ctx: Context) -> Result<u8, dev::Error> {
+                match ctx.access {
+                    Access::Fetch => Ok(u8::try_from(ctx.initiator)?),
+                    _ => self.try_read_dyn(index),
+                }
+            }
+        }
+
+        let mut bus = setup();
+        bus.map(0x300..=0x3ff, Rom.to_dynamic());
+        // Context is forwarded to the device
+        assert_eq!(bus.read_ctx(0x300, Context::new(Access::Fetch, 7)), 7);
+        assert_eq!(bus.read_ctx(0x300, Context::new(Access::Read, 7)), 0xff);
+        assert_eq!(bus.try_fetch(0x300), Ok(0));
+        // Debug accesses neither drive the bus nor trigger watchpoints
+        bus.set_open_bus(OpenBus::Latch);
+        bus.watch(0x000..=0x3ff, Access::Read, Action::Log);
+        bus.set_tracing(true);
+        assert_eq!(bus.read_ctx(0x100, Context::default()), 1);
+        assert_eq!(bus.read_ctx(0x200, Context::new(Access::Debug, 7)), 2);
+        assert_eq!(bus.read_ctx(0x400, Context::default()), 1);
+        assert_eq!(bus.take_log().len(), 1);
+    }
+
+    #[test]
+    fn contextual_dma_works() {
+        // DMA-blocked device
+        #[derive(Debug)]
+        struct Vram(u8);
+        impl Address<usize, u8> for Vram {
+            fn read(&self, _: usize) -> u8 {
+                self.0
+            }
+            fn write(&mut self, _: usize, value: u8) {
+                self.0 = value;
+            }
+        }
+        impl Block for Vram {}
+        impl Device<usize, u8> for Vram {
+            fn try_read_ctx(&self, index: usize, ctx: Context) -> Result<u8, dev::Error> {
+                match ctx.access {
+                    Access::Dma => Err("DMA blocked".into()),
+                    _ => self.try_read_dyn(index),
+                }
+            }
+            fn try_write_ctx(
+                &mut self,
+                index: usize,
+                value: u8,
+                ctx: Context,
+            ) -> Result<(), dev::Error> {
+                match ctx.access {
+                    Access::Dma => Err("DMA blocked".into()),
+                    _ => self.try_write_dyn(index, value),
+                }
+            }
+        }
+
+        // Nest the device within an inner bus
+        let mut inner = Bus::<usize, u8>::new();
+        inner.map(0x00..=0xff, Vram(0xaa).to_dynamic());
+        let mut bus = setup();
+        bus.map(0x300..=0x3ff, inner.to_dynamic());
+        bus.watch(0x000..=0x3ff, Access::Dma, Action::Log);
+        bus.set_tracing(true);
+        let dma = Context::new(Access::Dma, 1);
+        // DMA is forwarded through nested buses as its own kind
+        assert!(bus.try_read_ctx(0x300, dma).is_err());
+        assert!(bus.try_write_ctx(0x300, 0xbb, dma).is_err());
+        assert_eq!(bus.read(0x300), 0xaa);
+        // DMA transfers elsewhere are traced as such
+        bus.write_ctx(0x100, 0xcc, dma);
+        assert_eq!(bus.read_ctx(0x100, dma), 0xcc);
+        let event = Event {
+            index: 0x100,
+            ctx: dma,
+            value: 0xcc,
+        };
+        assert_eq!(bus.take_log(), [event, event]);
+    }
+
+    #[test]
+    #[should_panic(expected = "protection fault")]
+    fn contextual_fetch_protected_panics() {
+        let mut bus = setup();
+        let ram = bus.regions()[1].handle;
+        bus.protect(ram, Perm::RW);
+        assert_eq!(bus.read_ctx(0x100, Context::default()), 1);
+        bus.read_ctx(0x100, Context::new(Access::Fetch, 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "protection fault")]
+    fn address_write_protected_panics() {
+        let mut bus = setup();
+        let handle = bus.regions()[0].handle;
+        bus.protect(handle, Perm::R);
+        bus.write(0x000, 0xaa);
+    }
+
+    #[test]
+    fn watch_log_works() {
+        let mut bus = setup();
+        bus.watch(0x100..=0x1ff, Access::Write, Action::Log);
+        bus.watch(0x180..=0x27f, Access::Read, Action::Log);
+        // Disabled tracing records nothing
+        bus.write(0x100, 0xaa);
+        assert!(bus.take_log().is_empty());
+        // Enabled tracing records watched accesses
+        bus.set_tracing(true);
+        assert!(bus.tracing());
+        bus.write(0x0ff, 0xaa);
+        bus.write_ctx(0x100, 0xbb, Context::new(Access::Write, 3));
+        let _ = bus.read(0x100);
+        let _ = bus.read(0x200);
+        let _ = bus.try_fetch(0x200);
+        assert_eq!(
+            bus.take_log(),
+            [
+                Event {
+                    index: 0x100,
+                    ctx: Context::new(Access::Write, 3),
+                    value: 0xbb,
+                },
+                Event {
+                    index: 0x200,
+                    ctx: Context::default(),
+                    value: 2,
+                },
+            ]
+        );
+        assert!(bus.take_log().is_empty());
+    }
+
+    #[test]
+    fn watch_call_works() {
+        use std::rc::Rc;
+
+        let mut bus = setup();
+        let hits = Rc::new(Cell::new(0));
+        let watch = bus.watch(0x000..=0x2ff, Access::Fetch, {
+            let hits = hits.clone();
+            Action::Call(Box::new(move |event| {
+                assert_eq!(event.ctx.access, Access::Fetch);
+                hits.set(hits.get() + 1);
+            }))
+        });
+        bus.set_tracing(true);
+        (0x000..0x010).for_each(|index| {
+            let _ = bus.fetch(index);
+        });
+        let _ = bus.read(0x000);
+        assert_eq!(hits.get(), 0x10);
+        // Removed watchpoints are no longer triggered
+        assert!(bus.unwatch(watch));
+        assert!(!bus.unwatch(watch));
+        let _ = bus.fetch(0x000);
+        assert_eq!(hits.get(), 0x10);
+    }
+
+    #[test]
+    fn watch_call_reentrant_works() {
+        use std::rc::Rc;
+
+        let bus = setup().to_shared();
+        let hits = Rc::new(Cell::new(0));
+        bus.borrow_mut().watch(0x000..=0x2ff, Access::Read, {
+            let bus = bus.clone();
+            let hits = hits.clone();
+            Action::Call(Box::new(move |event| {
+                // Callbacks may access the bus, but are not re-entered
+                hits.set(hits.get() + 1);
+                assert_eq!(bus.read(event.index + 0x100), event.value + 1);
+            }))
+        });
+        bus.borrow_mut().set_tracing(true);
+        bus.borrow_mut()
+            .watch(0x100..=0x1ff, Access::Read, Action::Log);
+        assert_eq!(bus.read(0x000), 0);
+        assert_eq!(hits.get(), 1);
+        assert_eq!(bus.borrow_mut().take_log().len(), 1);
+    }
+
+    #[test]
+    fn device_block_works() {
+        let mut bus = Bus::<u16, u8>::new();
+        let lo = Ram::from(&[0; 0x100]).to_dynamic();
+        let hi = Ram::from(&[0; 0x100]).to_dynamic();
+        let mid = Ram::from(&[0; 0x010]).to_dynamic();
+        bus.map(0x0000..=0x00ff, lo.clone());
+        bus.map(0x0100..=0x01ff, hi.clone());
+        bus.map(0x00f8..=0x0107, mid.clone());
+        // Blocks are split across overlapping mappings
+        bus.write_block(0x00f0, &[0xaa; 0x20]);
+        let mut buf = [0; 0x10];
+        lo.read_block(0x00f0, &mut buf);
+        assert_eq!(buf[..0x08], [0xaa; 0x08]);
+        assert_eq!(buf[0x08..], [0x00; 0x08]);
+        mid.read_block(0x0000, &mut buf);
+        assert_eq!(buf[..0x08], [0xaa; 0x08]);
+        assert_eq!(buf[0x08..], [0x00; 0x08]);
+        hi.read_block(0x0000, &mut buf);
+        assert_eq!(buf, [0xaa; 0x10]);
+        let mut buf = [0; 0x30];
+        bus.read_block(0x00e8, &mut buf);
+        assert_eq!(buf[..0x08], [0x00; 0x08]);
+        assert_eq!(buf[0x08..0x28], [0xaa; 0x20]);
+        assert_eq!(buf[0x28..], [0x00; 0x08]);
+    }
+
+    #[test]
+    fn device_block_mirror_works() {
+        let mut bus = Bus::<u16, u8>::new();
+        let ram = Ram::from(&[0; 0x004]).to_dynamic();
+        bus.map_mirror(0x0000..=0x000f, 0x004, ram.clone());
+        bus.write_block(0x0002, &[0x11, 0x22, 0x33, 0x44]);
+        let mut buf = [0; 4];
+        ram.read_block(0x0000, &mut buf);
+        assert_eq!(buf, [0x33, 0x44, 0x11, 0x22]);
+        let mut buf = [0; 0x10];
+        bus.read_block(0x0000, &mut buf);
+        assert_eq!(buf, [0x33, 0x44, 0x11, 0x22].repeat(4)[..]);
+    }
+
+    #[test]
+    fn device_block_open_bus_works() {
+        let mut bus = Bus::<u16, u8>::new();
+        bus.map(0x0000..=0x0007, Ram::from(&[0xaa; 0x08]).to_dynamic());
+        bus.set_open_bus(OpenBus::Latch);
+        let mut buf = [0; 0x10];
+        bus.read_block(0x0000, &mut buf);
+        assert_eq!(buf, [0xaa; 0x10]);
+        bus.write_block(0x0004, &[0xbb; 0x08]);
+        bus.read_block(0x0000, &mut buf);
+        assert_eq!(buf[..0x04], [0xaa; 0x04]);
+        assert_eq!(buf[0x04..], [0xbb; 0x0c]);
+    }
+
+    #[test]
+    #[should_panic(expected = "index is not mapped")]
+    fn device_read_block_unmapped_panics() {
+        let bus = setup();
+        let mut buf = [0; 0x10];
+        bus.read_block(0x3f8, &mut buf);
+    }
+
+    #[test]
+    fn try_block_works() {
+        let mut bus = setup();
+        let rom = bus.regions()[1].handle;
+        bus.protect(rom, Perm::R);
+        let mut buf = [0; 0x10];
+        assert_eq!(bus.try_read_block(0x0f8, &mut buf), Ok(()));
+        assert_eq!(buf[..0x08], [0; 0x08]);
+        assert_eq!(buf[0x08..], [1; 0x08]);
+        // Faults are reported before any access is performed
+        assert_eq!(
+            bus.try_write_block(0x0f8, &[0xaa; 0x10]),
+            Err(Error::Protected {
+                index: 0x100,
+                perm: Perm::W,
+            })
+        );
+        assert_eq!(bus.read(0x0f8), 0);
+        assert_eq!(
+            bus.try_read_block(0x2f8, &mut buf),
+            Err(Error::Unmapped(0x300))
+        );
+    }
+
+    #[test]
+    fn try_block_device_fails() {
+        let mut bus = setup();
+        bus.map(0x400..=0x4ff, Rom::<u8, 0x100>::new().to_dynamic());
+        bus.map(0x500..=0x5ff, Ram::<u8, 0x10>::new().to_dynamic());
+        // Writing a block into a ROM is an error
+        assert!(matches!(
+            bus.try_write_block(0x480, &[0xaa; 0x10]),
+            Err(Error::Device {
+                index: 0x480,
+                offset: 0x80,
+                ..
+            })
+        ));
+        // As is a block beyond the end of a device
+        let mut buf = [0; 0x20];
+        assert!(matches!(
+            bus.try_read_block(0x500, &mut buf),
+            Err(Error::Device {
+                index: 0x500,
+                offset: 0x00,
+                ..
+            })
+        ));
+        assert!(matches!(
+            bus.try_write_block(0x508, &[0xbb; 0x10]),
+            Err(Error::Device { index: 0x508, .. })
+        ));
+        assert_eq!(bus.read(0x508), 0x00);
+    }
+
+    #[test]
+    fn device_dirty_works() {
+        let mut bus = Bus::<u16, u8>::new();
+        let mut wram = Ram::<u8, 0x800>::new();
+        wram.track(8);
+        let mut vram = Ram::<u8, 0x100>::new();
+        vram.track(4);
+        let rom = Rom::<u8, 0x100>::new();
+        bus.map_mirror(0x0000..=0x1fff, 0x800, wram.to_dynamic());
+        bus.map(0x2000..=0x20ff, vram.to_dynamic());
+        bus.map(0x2100..=0x21ff, rom.to_dynamic());
+        assert!(bus.dirty().is_empty());
+        // Writes are reported within every mirror
+        bus.write(0x0801, 0xaa);
+        bus.write(0x2042, 0xbb);
+        assert_eq!(
+            bus.dirty(),
+            [
+                0x0000..=0x00ff,
+                0x0800..=0x08ff,
+                0x1000..=0x10ff,
+                0x1800..=0x18ff,
+                0x2040..=0x204f,
+            ]
+        );
+        bus.clean();
+        assert!(bus.dirty().is_empty());
+    }
+
     #[test]
     fn address_read_mapped_works() {
         let bus = setup();
@@ -220,6 +1470,36 @@ mod tests {
         bus.read(0x301);
     }
 
+    #[test]
+    fn try_read_device_error_works() {
+        let mut bus = setup();
+        bus.map(0x300..=0x3ff, Ram::from(&[3; 0x80]).to_dynamic());
+        assert_eq!(bus.try_read(0x37f), Ok(3));
+        let Err(Error::Device {
+            index,
+            offset,
+            source,
+        }) = bus.try_read(0x380)
+        else {
+            panic!("expected device error");
+        };
+        assert_eq!(index, 0x380);
+        assert_eq!(offset, 0x080);
+        assert_eq!(source.to_string(), "index out of bounds: 128");
+    }
+
+    #[test]
+    fn try_write_device_error_works() {
+        let mut bus = setup();
+        bus.map(0x300..=0x3ff, Rom::from(&[3; 0x100]).to_dynamic());
+        let Err(Error::Device { index, offset, .. }) = bus.try_write(0x342, 0) else {
+            panic!("expected device error");
+        };
+        assert_eq!(index, 0x342);
+        assert_eq!(offset, 0x042);
+        assert_eq!(bus.read(0x342), 3);
+    }
+
     #[test]
     fn address_write_mapped_works() {
         let mut bus = setup();
@@ -234,6 +1514,143 @@ mod tests {
         bus.write(0x301, 4);
     }
 
+    #[test]
+    fn open_bus_fixed_works() {
+        let mut bus = setup();
+        bus.set_open_bus(OpenBus::Fixed(0xff));
+        assert_eq!(bus.read(0x301), 0xff);
+        bus.write(0x301, 0x00);
+        assert_eq!(bus.read(0x301), 0xff);
+        assert_eq!(bus.try_read(0x301), Err(Error::Unmapped(0x301)));
+    }
+
+    #[test]
+    fn open_bus_latch_works() {
+        let mut bus = setup();
+        bus.set_open_bus(OpenBus::Latch);
+        assert_eq!(bus.read(0x100), 1);
+        assert_eq!(bus.read(0x301), 1);
+        bus.write(0x000, 0xaa);
+        assert_eq!(bus.read(0x301), 0xaa);
+        // Dropped writes are still driven on the bus
+        bus.write(0x302, 0x55);
+        assert_eq!(bus.read(0x301), 0x55);
+        assert_eq!(bus.read(0x000), 0xaa);
+    }
+
+    #[test]
+    fn open_bus_nested_works() {
+        let mut inner = Bus::<usize, u8>::new();
+        inner.map(0x00..=0x0f, Ram::from(&[1; 0x10]).to_dynamic());
+        inner.set_open_bus(OpenBus::Fixed(0xff));
+        let mut bus = Bus::new();
+        bus.map(0x100..=0x1ff, inner.to_dynamic());
+        assert_eq!(bus.read(0x100), 1);
+        // Unmapped indices of the nested bus are handled by its policy
+        assert_eq!(bus.read(0x110), 0xff);
+        assert_eq!(bus.try_read(0x110).ok(), Some(0xff));
+        bus.write(0x110, 0xaa);
+        // Unmapped indices of the outer bus are not
+        assert!(matches!(bus.try_read(0x200), Err(Error::Unmapped(0x200))));
+    }
+
+    #[test]
+    fn open_bus_random_works() {
+        let mut bus = setup();
+        bus.set_open_bus(OpenBus::random());
+        // NOTE: This should only fail with negligible probability.
+        assert!((0x300..0x400).any(|index| bus.read(index) != bus.read(index)));
+        bus.write(0x301, 0xaa);
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    #[test]
+    fn open_bus_custom_works() {
+        let mut bus = setup();
+        bus.set_open_bus(OpenBus::custom(|index: usize| (index >> 8) as u8));
+        assert_eq!(bus.read(0x301), 0x03);
+        assert_eq!(bus.read(0x4ff), 0x04);
+        bus.write(0x301, 0xaa);
+        assert_eq!(bus.read(0x301), 0x03);
+    }
+
+    #[test]
+    fn regions_works() {
+        let mut bus = setup();
+        let dev = Ram::from(&[3; 0x10]).to_dynamic();
+        bus.map(0x180..=0x18f, dev.clone());
+        let regions = bus.regions();
+        assert_eq!(
+            regions
+                .iter()
+                .map(|it| (it.range.clone(), it.prio))
+                .collect::<Vec<_>>(),
+            [
+                (0x000..=0x0ff, 3),
+                (0x100..=0x1ff, 2),
+                (0x180..=0x18f, 1),
+                (0x200..=0x2ff, 0),
+            ]
+        );
+        assert_eq!(regions[2].dev, dev);
+    }
+
+    #[test]
+    fn effective_works() {
+        let mut bus = setup();
+        let dev = Ram::from(&[3; 0x10]).to_dynamic();
+        bus.map(0x180..=0x18f, dev.clone());
+        bus.map(0x280..=0x2ff, dev.clone());
+        let effective = bus.effective();
+        assert_eq!(
+            effective
+                .iter()
+                .map(|it| it.range.clone())
+                .collect::<Vec<_>>(),
+            [
+                0x000..=0x0ff,
+                0x100..=0x17f,
+                0x180..=0x18f,
+                0x190..=0x1ff,
+                0x200..=0x27f,
+                0x280..=0x2ff,
+            ]
+        );
+        assert_eq!(effective[1].dev, effective[3].dev);
+        assert_eq!(effective[2].dev, dev);
+        assert_eq!(effective[5].dev, dev);
+        // Effective regions should resolve identically to the bus
+        for region in &effective {
+            for index in region.range.clone() {
+                assert_eq!(bus.get(index).as_ref(), Some(&region.dev));
+            }
+        }
+    }
+
+    #[test]
+    fn effective_nested_works() {
+        let mut bus = Bus::<u16, u8>::new();
+        for range in [0x000..=0x3ff, 0x100..=0x1ff, 0x140..=0x17f, 0x300..=0x30f] {
+            bus.map(range, Ram::from(&[0; 0x400]).to_dynamic());
+        }
+        let effective = bus.effective();
+        assert_eq!(
+            effective
+                .iter()
+                .map(|it| (it.range.clone(), it.prio))
+                .collect::<Vec<_>>(),
+            [
+                (0x000..=0x0ff, 3),
+                (0x100..=0x13f, 2),
+                (0x140..=0x17f, 1),
+                (0x180..=0x1ff, 2),
+                (0x200..=0x2ff, 3),
+                (0x300..=0x30f, 0),
+                (0x310..=0x3ff, 3),
+            ]
+        );
+    }
+
     #[allow(clippy::range_minus_one)]
     #[allow(clippy::reversed_empty_ranges)]
     #[test]
@@ -301,4 +1718,64 @@ mod tests {
             .map(|index| bus.read(index))
             .all(|byte| byte == 0xff));
     }
+
+    #[test]
+    fn with_granularity_works() {
+        for bits in [0, 4, 8, 12] {
+            let mut bus = Bus::<usize, u8>::with_granularity(bits);
+            bus.map(0x000..=0x0ff, Ram::from(&[0; 0x100]).to_dynamic());
+            bus.map(0x100..=0x1ff, Ram::from(&[1; 0x100]).to_dynamic());
+            bus.map(0x180..=0x187, Ram::from(&[2; 0x008]).to_dynamic());
+            (0x000..0x100).for_each(|i| assert_eq!(bus.read(i), 0));
+            (0x100..0x180).for_each(|i| assert_eq!(bus.read(i), 1));
+            (0x180..0x188).for_each(|i| assert_eq!(bus.read(i), 2));
+            (0x188..0x200).for_each(|i| assert_eq!(bus.read(i), 1));
+            assert_eq!(bus.try_read(0x200), Err(Error::Unmapped(0x200)));
+        }
+    }
+
+    #[test]
+    fn page_table_matches_search() {
+        let mut bus = Bus::<u16, u8>::with_granularity(4);
+        let devs: Vec<Dynamic<u16, u8>> =
+            (0..6).map(|i| Ram::from(&[i; 0x40]).to_dynamic()).collect();
+        for (dev, range) in devs.iter().zip([
+            0x00..=0x3f,
+            0x08..=0x0b,
+            0x10..=0x4f,
+            0x12..=0x12,
+            0x30..=0x6f,
+            0x31..=0x3e,
+        ]) {
+            bus.map(range, dev.clone());
+        }
+        let check = |bus: &Bus<u16, u8>| {
+            for idx in 0x00..=0x80 {
+                assert_eq!(
+                    bus.maps.get(idx).map(|it| &it.entry),
+                    bus.maps.search(idx).map(|it| &it.entry),
+                    "mismatch at {idx:#04x}"
+                );
+            }
+        };
+        check(&bus);
+        // Remove mappings one by one
+        for dev in [&devs[2], &devs[5], &devs[0]] {
+            assert!(bus.unmap(dev).is_some());
+            check(&bus);
+        }
+    }
+
+    #[test]
+    fn page_table_beyond_limit_works() {
+        let mut bus = Bus::<usize, u8>::new();
+        bus.map(0x000..=0x0ff, Ram::from(&[0; 0x100]).to_dynamic());
+        bus.map(
+            usize::MAX - 0xff..=usize::MAX,
+            Ram::from(&[1; 0x100]).to_dynamic(),
+        );
+        assert_eq!(bus.read(0x00), 0);
+        assert_eq!(bus.read(usize::MAX), 1);
+        assert_eq!(bus.try_read(0x100), Err(Error::Unmapped(0x100)));
+    }
 }
@@ -1,4 +1,7 @@
-use super::{Error, Range};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use super::{Decode, Error, Perm, Range};
 use crate::arch::{TryAddress, Value};
 use crate::dev::{Device, Dynamic};
 
@@ -14,10 +17,195 @@ where
     fn get(&self, index: Idx) -> Option<Dynamic<Idx, V>>;
 
     /// Maps a device to the provided range.
-    fn map(&mut self, range: Range<Idx>, dev: Dynamic<Idx, V>);
+    ///
+    /// Returns a handle that uniquely identifies the mapping.
+    fn map(&mut self, range: Range<Idx>, dev: Dynamic<Idx, V>) -> Handle;
 
     /// Unmaps and returns a device.
     ///
     /// Returns `None` if device is not mapped.
+    ///
+    /// # Note
+    ///
+    /// Devices are compared by identity. If the device is mapped more than
+    /// once, only one of its mappings is removed.
     fn unmap(&mut self, dev: &Dynamic<Idx, V>) -> Option<Dynamic<Idx, V>>;
+
+    /// Maps a labelled device to the provided range.
+    ///
+    /// See [`Mux::map`] and [`Mux::label`].
+    fn map_labeled(&mut self, range: Range<Idx>, dev: Dynamic<Idx, V>, label: String) -> Handle {
+        let handle = self.map(range, dev);
+        self.label(handle, Some(label));
+        handle
+    }
+
+    /// Unmaps and returns the device of a mapping.
+    ///
+    /// Returns `None` if the mapping does not exist.
+    ///
+    /// # Note
+    ///
+    /// The provided implementation does not support handles, and so always
+    /// returns `None`.
+    fn unmap_handle(&mut self, handle: Handle) -> Option<Dynamic<Idx, V>> {
+        let _ = handle;
+        None
+    }
+
+    /// Unmaps and returns all devices mapped entirely within the range.
+    ///
+    /// # Note
+    ///
+    /// The provided implementation does not support ranges, and so never
+    /// unmaps any devices.
+    fn unmap_range(&mut self, range: Range<Idx>) -> Vec<Dynamic<Idx, V>> {
+        let _ = range;
+        Vec::new()
+    }
+
+    /// Moves a mapping to the provided range.
+    ///
+    /// Returns `false` if the mapping does not exist.
+    ///
+    /// # Note
+    ///
+    /// The provided implementation does not support handles, and so always
+    /// returns `false`.
+    fn remap(&mut self, handle: Handle, range: Range<Idx>) -> bool {
+        let _ = (handle, range);
+        false
+    }
+
+    /// Labels a mapping, replacing any existing label.
+    ///
+    /// Returns `false` if the mapping does not exist.
+    ///
+    /// # Note
+    ///
+    /// The provided implementation does not support labels, and so always
+    /// returns `false`.
+    fn label(&mut self, handle: Handle, label: Option<String>) -> bool {
+        let _ = (handle, label);
+        false
+    }
+
+    /// Lists all mapped regions, ordered by address.
+    ///
+    /// Overlapping regions are all included; use [`Mux::effective`] to list
+    /// only those visible.
+    ///
+    /// # Note
+    ///
+    /// The provided implementation does not expose its mappings, and so always
+    /// returns an empty list.
+    fn regions(&self) -> Vec<Region<Idx, V>> {
+        Vec::new()
+    }
+
+    /// Lists the effective memory map, ordered by address.
+    ///
+    /// Overlapping regions are flattened, such that each returned region is
+    /// the visible subset of its mapping.
+    fn effective(&self) -> Vec<Region<Idx, V>> {
+        flatten(&self.regions())
+    }
+}
+
+/// Mapping handle.
+///
+/// Uniquely identifies a mapping within a [`Mux`].
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Handle(pub(super) usize);
+
+/// Mapped device region.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Region<Idx, V>
+where
+    Idx: Value,
+    V: Value,
+{
+    /// Mapping handle.
+    pub handle: Handle,
+    /// Mapping label.
+    pub label: Option<String>,
+    /// Mapping address decoding.
+    pub decode: Decode<Idx>,
+    /// Mapping access permissions.
+    pub perm: Perm,
+    /// Mapped address range.
+    pub range: Range<Idx>,
+    /// Mapped device.
+    pub dev: Dynamic<Idx, V>,
+    /// Mapping priority.
+    ///
+    /// Where regions overlap, the one with the lowest priority is visible.
+    pub prio: usize,
+}
+
+/// Flattens overlapping regions into those visible.
+///
+/// Sweeps across the sorted region boundaries, tracking the regions active at
+/// each in a heap ordered by priority, such that flattening `n` regions takes
+/// `O(n log n)` time.
+pub(super) fn flatten<Idx, V>(regions: &[Region<Idx, V>]) -> Vec<Region<Idx, V>>
+where
+    Idx: Value,
+    V: Value,
+{
+    // Collect every index at which the visible region may change
+    let mut edges: Vec<Idx> = regions
+        .iter()
+        .flat_map(|it| {
+            [
+                Some(*it.range.start()),
+                it.range.end().checked_add(&Idx::one()),
+            ]
+        })
+        .flatten()
+        .collect();
+    edges.sort_unstable();
+    edges.dedup();
+    // Order regions by their start
+    let mut order: Vec<usize> = (0..regions.len()).collect();
+    order.sort_by_key(|&i| *regions[i].range.start());
+    let mut order = order.into_iter().peekable();
+
+    // Determine the winner of each segment between edges
+    let mut active = BinaryHeap::new();
+    let mut flat: Vec<Region<Idx, V>> = Vec::new();
+    for (i, &start) in edges.iter().enumerate() {
+        // Activate regions starting at this edge
+        while let Some(j) = order.next_if(|&j| *regions[j].range.start() <= start) {
+            active.push(Reverse((regions[j].prio, j)));
+        }
+        // Retire the highest priority regions which have ended
+        while let Some(&Reverse((_, j))) = active.peek() {
+            if *regions[j].range.end() >= start {
+                break;
+            }
+            active.pop();
+        }
+        let Some(&Reverse((_, j))) = active.peek() else {
+            continue;
+        };
+        let win = &regions[j];
+        let end = edges
+            .get(i + 1)
+            .map_or(*win.range.end(), |&next| next - Idx::one());
+        match flat.last_mut() {
+            // Extend adjacent segments of the same region
+            Some(last)
+                if last.prio == win.prio
+                    && last.range.end().checked_add(&Idx::one()) == Some(start) =>
+            {
+                last.range = *last.range.start()..=end;
+            }
+            _ => flat.push(Region {
+                range: start..=end,
+                ..win.clone()
+            }),
+        }
+    }
+    flat
 }
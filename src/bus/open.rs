@@ -0,0 +1,108 @@
+use std::fmt::Debug;
+use std::rc::Rc;
+
+use rand::distributions::Standard;
+use rand::prelude::Distribution;
+
+use crate::arch::Value;
+
+/// Open-bus behaviour.
+///
+/// # Usage
+///
+/// Determines how a [`Bus`](super::Bus) responds to accesses at an unmapped
+/// index through [`Address`](crate::Address), or through
+/// [`Device`](crate::dev::Device) when nested within another bus. Other than
+/// under [`OpenBus::Panic`], unmapped writes are silently dropped.
+///
+/// Fallible accesses through [`TryAddress`](crate::TryAddress) are unaffected,
+/// and always report unmapped indices as an error.
+#[derive(Default)]
+pub enum OpenBus<Idx, V>
+where
+    Idx: Value,
+    V: Value,
+{
+    /// Panic on unmapped accesses.
+    #[default]
+    Panic,
+    /// Yield a fixed value, such as from pull-up resistors.
+    Fixed(V),
+    /// Yield the last value driven on the bus.
+    Latch,
+    /// Yield random noise.
+    Random(fn() -> V),
+    /// Yield a value computed from the index.
+    Custom(Rc<dyn Fn(Idx) -> V>),
+}
+
+impl<Idx, V> OpenBus<Idx, V>
+where
+    Idx: Value,
+    V: Value,
+{
+    /// Constructs an `OpenBus` that yields random noise.
+    #[must_use]
+    pub fn random() -> Self
+    where
+        Standard: Distribution<V>,
+    {
+        Self::Random(rand::random)
+    }
+
+    /// Constructs an `OpenBus` that yields values computed by the provided
+    /// function.
+    pub fn custom(f: impl Fn(Idx) -> V + 'static) -> Self {
+        Self::Custom(Rc::new(f))
+    }
+
+    /// Resolves the value of an unmapped read, given the last value driven.
+    ///
+    /// Returns `None` if unmapped reads should panic.
+    pub(super) fn read(&self, index: Idx, last: V) -> Option<V> {
+        match self {
+            Self::Panic => None,
+            Self::Fixed(value) => Some(*value),
+            Self::Latch => Some(last),
+            Self::Random(f) => Some(f()),
+            Self::Custom(f) => Some(f(index)),
+        }
+    }
+
+    /// Checks if unmapped accesses are recovered from, rather than panicking.
+    pub(super) fn recovers(&self) -> bool {
+        !matches!(self, Self::Panic)
+    }
+}
+
+impl<Idx, V> Clone for OpenBus<Idx, V>
+where
+    Idx: Value,
+    V: Value,
+{
+    fn clone(&self) -> Self {
+        match self {
+            Self::Panic => Self::Panic,
+            Self::Fixed(value) => Self::Fixed(*value),
+            Self::Latch => Self::Latch,
+            Self::Random(f) => Self::Random(*f),
+            Self::Custom(f) => Self::Custom(f.clone()),
+        }
+    }
+}
+
+impl<Idx, V> Debug for OpenBus<Idx, V>
+where
+    Idx: Value,
+    V: Value,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Panic => write!(f, "Panic"),
+            Self::Fixed(value) => f.debug_tuple("Fixed").field(value).finish(),
+            Self::Latch => write!(f, "Latch"),
+            Self::Random(_) => write!(f, "Random"),
+            Self::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
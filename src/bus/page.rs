@@ -0,0 +1,181 @@
+use std::fmt::Debug;
+
+use super::map::{Entry, Mapping};
+use crate::arch::Value;
+
+/// Default page granularity, in address bits.
+pub(super) const BITS: u32 = 8;
+
+/// Maximum number of pages tracked by a table.
+///
+/// Addresses beyond the last page are not cached, and must instead be resolved
+/// by searching the map directly.
+const LIMIT: usize = 1 << 16;
+
+/// Page table.
+///
+/// Caches the winning mappings within each fixed-size page of the address
+/// space, allowing lookups to be resolved in constant time.
+///
+/// # Note
+///
+/// Only lookups within a [whole](Page::Whole) page are constant time. Those
+/// within a [split](Page::Split) page search its visible mappings linearly,
+/// and those beyond the first [`LIMIT`] pages are resolved by searching the
+/// map.
+#[derive(Debug)]
+pub(super) struct Table<Idx, V>
+where
+    Idx: Value,
+    V: Entry,
+{
+    bits: u32,
+    pages: Vec<Page<Idx, V>>,
+}
+
+impl<Idx, V> Table<Idx, V>
+where
+    Idx: Value,
+    V: Entry,
+{
+    /// Constructs a new, empty `Table` with the specified page granularity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if pages of `2^bits` addresses cannot be represented.
+    pub(super) fn with_granularity(bits: u32) -> Self {
+        assert!(
+            bits < usize::BITS - LIMIT.trailing_zeros(),
+            "page granularity too large: {bits}"
+        );
+        Self {
+            bits,
+            pages: Vec::new(),
+        }
+    }
+
+    /// Clears the table, removing all pages.
+    pub(super) fn clear(&mut self) {
+        self.pages.clear();
+    }
+
+    /// Gets the page containing the index.
+    ///
+    /// Returns `None` if the index is not covered by the table.
+    pub(super) fn get(&self, idx: Idx) -> Option<&Page<Idx, V>> {
+        self.pages.get(idx.to_usize()? >> self.bits)
+    }
+
+    /// Returns the indices of the pages overlapping the range.
+    ///
+    /// Pages beyond the table's limit are excluded.
+    pub(super) fn span(&self, start: Idx, end: Idx) -> std::ops::Range<usize> {
+        let page = |idx: Idx| {
+            idx.to_usize()
+                .map_or(if idx < Idx::zero() { 0 } else { LIMIT }, |idx| {
+                    (idx >> self.bits).min(LIMIT)
+                })
+        };
+        page(start)..page(end).saturating_add(1).min(LIMIT)
+    }
+
+    /// Returns the inclusive bounds of a page.
+    ///
+    /// Returns `None` if the page's lower bound cannot be addressed.
+    pub(super) fn bounds(&self, page: usize) -> Option<(Idx, Idx)> {
+        let start = page << self.bits;
+        let end = start | ((1 << self.bits) - 1);
+        Some((
+            Idx::from(start)?,
+            Idx::from(end).unwrap_or_else(Idx::max_value),
+        ))
+    }
+
+    /// Replaces the contents of a page, growing the table as needed.
+    pub(super) fn set(&mut self, page: usize, data: Page<Idx, V>) {
+        if page >= self.pages.len() {
+            if let Page::Empty = data {
+                return;
+            }
+            self.pages.resize_with(page + 1, Default::default);
+        }
+        self.pages[page] = data;
+        // Trim trailing empty pages
+        while let Some(Page::Empty) = self.pages.last() {
+            self.pages.pop();
+        }
+    }
+}
+
+impl<Idx, V> Default for Table<Idx, V>
+where
+    Idx: Value,
+    V: Entry,
+{
+    fn default() -> Self {
+        Self::with_granularity(BITS)
+    }
+}
+
+/// Page table entry.
+#[derive(Debug, Default)]
+pub(super) enum Page<Idx, V>
+where
+    Idx: Value,
+    V: Entry,
+{
+    /// Nothing is mapped within the page.
+    #[default]
+    Empty,
+    /// A single mapping wins across the entire page.
+    Whole(Mapping<Idx, V>),
+    /// Several mappings are visible within the page, in order of priority.
+    ///
+    /// Lookups search these linearly, taking time proportional to the number
+    /// of mappings overlapping the page.
+    Split(Vec<Mapping<Idx, V>>),
+}
+
+impl<Idx, V> Page<Idx, V>
+where
+    Idx: Value,
+    V: Entry,
+{
+    /// Constructs a page from its overlapping mappings.
+    ///
+    /// Mappings must be provided in order of priority, and are only retained
+    /// until one is found that covers the entire page.
+    pub(super) fn build<'a>(
+        start: Idx,
+        end: Idx,
+        maps: impl IntoIterator<Item = &'a Mapping<Idx, V>>,
+    ) -> Self
+    where
+        V: 'a,
+    {
+        let mut vis = Vec::new();
+        for map in maps {
+            let full = map.base() <= start && end <= *map.range.end();
+            vis.push(map.clone());
+            if full {
+                break;
+            }
+        }
+        match vis.len() {
+            0 => Self::Empty,
+            1 if vis[0].base() <= start && end <= *vis[0].range.end() => {
+                Self::Whole(vis.pop().unwrap())
+            }
+            _ => Self::Split(vis),
+        }
+    }
+
+    /// Finds the winning mapping for the index.
+    pub(super) fn find(&self, idx: Idx) -> Option<&Mapping<Idx, V>> {
+        match self {
+            Page::Empty => None,
+            Page::Whole(map) => Some(map),
+            Page::Split(maps) => maps.iter().find(|it| it.contains(&idx)),
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use std::fmt::Display;
+use std::ops::{BitAnd, BitOr, Not};
+
+/// Mapping access permissions.
+///
+/// # Usage
+///
+/// Permissions are combined as a set of flags, checked by the
+/// [`Bus`](super::Bus) before accessing a mapped device:
+///
+/// | Flag        | Access                                           |
+/// |-------------|--------------------------------------------------|
+/// | [`Perm::R`] | [`try_read`](crate::TryAddress::try_read)        |
+/// | [`Perm::W`] | [`try_write`](crate::TryAddress::try_write)      |
+/// | [`Perm::X`] | [`try_fetch`](super::Bus::try_fetch)             |
+///
+/// By default, mappings are granted all permissions.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Perm(u8);
+
+impl Perm {
+    /// No permissions.
+    pub const NONE: Self = Self(0b000);
+    /// Read permission.
+    pub const R: Self = Self(0b001);
+    /// Write permission.
+    pub const W: Self = Self(0b010);
+    /// Execute permission.
+    pub const X: Self = Self(0b100);
+    /// Read and write permissions.
+    pub const RW: Self = Self(Self::R.0 | Self::W.0);
+    /// Read and execute permissions.
+    pub const RX: Self = Self(Self::R.0 | Self::X.0);
+    /// All permissions.
+    pub const RWX: Self = Self(Self::R.0 | Self::W.0 | Self::X.0);
+
+    /// Checks if all permissions within `other` are granted.
+    #[must_use]
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitAnd for Perm {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl BitOr for Perm {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl Default for Perm {
+    fn default() -> Self {
+        Self::RWX
+    }
+}
+
+impl Display for Perm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (perm, flag) in [(Self::R, 'r'), (Self::W, 'w'), (Self::X, 'x')] {
+            write!(f, "{}", if self.contains(perm) { flag } else { '-' })?;
+        }
+        Ok(())
+    }
+}
+
+impl Not for Perm {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        Self(!self.0 & Self::RWX.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contains_works() {
+        assert!(Perm::RWX.contains(Perm::RX));
+        assert!(Perm::RX.contains(Perm::X));
+        assert!(!Perm::RX.contains(Perm::W));
+        assert!(Perm::NONE.contains(Perm::NONE));
+    }
+
+    #[test]
+    fn ops_works() {
+        assert_eq!(Perm::R | Perm::W, Perm::RW);
+        assert_eq!(Perm::RW & Perm::RX, Perm::R);
+        assert_eq!(!Perm::W, Perm::RX);
+    }
+
+    #[test]
+    fn display_works() {
+        assert_eq!(Perm::RWX.to_string(), "rwx");
+        assert_eq!(Perm::RX.to_string(), "r-x");
+        assert_eq!(Perm::NONE.to_string(), "---");
+    }
+}
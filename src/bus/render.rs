@@ -0,0 +1,301 @@
+//! Memory map rendering.
+//!
+//! # Usage
+//!
+//! The [`Table`] and [`Diagram`] renderers both implement [`Display`], and so
+//! can be used to print the [regions](Region) of a [`Mux`](super::Mux) in a
+//! human-readable format:
+//!
+//! ```
+//! use remus::bus::render::{Diagram, Table};
+//! use remus::bus::{Bus, Mux};
+//! use remus::dev::Device;
+//! use remus::mem::Ram;
+//!
+//! let mut bus = Bus::<u16, u8>::new();
+//! bus.map(0x0000..=0x7fff, Ram::<u8, 0x8000>::new().to_dynamic());
+//! bus.map(0x4000..=0x4fff, Ram::<u8, 0x1000>::new().to_dynamic());
+//!
+//! let regions = bus.regions();
+//! println!("{}", Table::new(&regions));
+//! println!("{}", Diagram::new(&regions));
+//! ```
+
+use std::fmt::{Display, Formatter, Result};
+
+use super::mux::{flatten, Region};
+use crate::arch::Value;
+use crate::dev::Dynamic;
+
+/// Glyphs used to represent devices within a [`Diagram`].
+const GLYPHS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+/// Tabular memory map renderer.
+///
+/// Renders each region on its own row, listing its bounds, priority,
+/// permissions, and device. Devices are named by their mapping's label, if any.
+#[derive(Debug)]
+pub struct Table<'a, Idx, V>
+where
+    Idx: Value,
+    V: Value,
+{
+    regions: &'a [Region<Idx, V>],
+}
+
+impl<'a, Idx, V> Table<'a, Idx, V>
+where
+    Idx: Value,
+    V: Value,
+{
+    /// Constructs a new `Table` of the provided regions.
+    #[must_use]
+    pub fn new(regions: &'a [Region<Idx, V>]) -> Self {
+        Self { regions }
+    }
+}
+
+impl<Idx, V> Display for Table<'_, Idx, V>
+where
+    Idx: Value,
+    V: Value,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let names = Names::new(self.regions);
+        let width = 2 + 2 * std::mem::size_of::<Idx>();
+        writeln!(
+            f,
+            "{:<width$}   {:<width$}   {:>4}   PERM   DEVICE",
+            "START", "END", "PRIO"
+        )?;
+        for region in self.regions {
+            writeln!(
+                f,
+                "{:<width$} - {:<width$}   {:>4}   {}    {}",
+                hex(*region.range.start()),
+                hex(*region.range.end()),
+                region.prio,
+                region.perm,
+                names.name(region),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Diagrammatic memory map renderer.
+///
+/// Renders each region on its own row, scaled to fit within the configured
+/// width, followed by the effective memory map:
+///
+/// ```text
+///     ┌────────────────┐
+/// D0: │ aaaaaaaa       │
+/// D1: │     bbbb       │
+///     ├────────────────┤
+///     │ aaaabbbb       │
+///     └────────────────┘
+/// ```
+#[derive(Debug)]
+pub struct Diagram<'a, Idx, V>
+where
+    Idx: Value,
+    V: Value,
+{
+    regions: &'a [Region<Idx, V>],
+    width: usize,
+}
+
+impl<'a, Idx, V> Diagram<'a, Idx, V>
+where
+    Idx: Value,
+    V: Value,
+{
+    /// Constructs a new `Diagram` of the provided regions.
+    #[must_use]
+    pub fn new(regions: &'a [Region<Idx, V>]) -> Self {
+        Self { regions, width: 64 }
+    }
+
+    /// Sets the maximum width of the diagram, in columns.
+    #[must_use]
+    pub fn width(self, width: usize) -> Self {
+        Self {
+            width: width.max(1),
+            ..self
+        }
+    }
+}
+
+impl<Idx, V> Display for Diagram<'_, Idx, V>
+where
+    Idx: Value,
+    V: Value,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let names = Names::new(self.regions);
+        let flat = flatten(self.regions);
+        // Determine the diagram's scale
+        let lo = self
+            .regions
+            .iter()
+            .map(|it| num(*it.range.start()))
+            .min()
+            .unwrap_or_default();
+        let hi = self
+            .regions
+            .iter()
+            .map(|it| num(*it.range.end()))
+            .max()
+            .unwrap_or_default();
+        let span = (hi - lo).saturating_add(1);
+        let cols = span.min(self.width as u128);
+        let edge = |col: u128| lo + span / cols * col + span % cols * col / cols;
+        let buckets: Vec<(u128, u128)> = (0..cols)
+            .map(|col| (edge(col), edge(col + 1).saturating_sub(1).max(edge(col))))
+            .collect();
+        let label = self
+            .regions
+            .iter()
+            .map(|it| names.name(it).chars().count())
+            .max()
+            .unwrap_or_default();
+        let bar = "─".repeat(buckets.len() + 2);
+        // Draw each region
+        writeln!(f, "{:label$}  ┌{bar}┐", "")?;
+        for region in self.regions {
+            let id = names.id(&region.dev);
+            let (start, end) = (num(*region.range.start()), num(*region.range.end()));
+            let row: String = buckets
+                .iter()
+                .map(|&(lo, hi)| {
+                    if start <= hi && lo <= end {
+                        char::from(GLYPHS[id % GLYPHS.len()])
+                    } else {
+                        ' '
+                    }
+                })
+                .collect();
+            writeln!(f, "{:<label$}: │ {row} │", names.name(region))?;
+        }
+        // Draw the effective map
+        writeln!(f, "{:label$}  ├{bar}┤", "")?;
+        let row: String = buckets
+            .iter()
+            .map(|&(lo, _)| {
+                flat.iter()
+                    .find(|it| num(*it.range.start()) <= lo && lo <= num(*it.range.end()))
+                    .map_or(' ', |it| {
+                        char::from(GLYPHS[names.id(&it.dev) % GLYPHS.len()])
+                    })
+            })
+            .collect();
+        writeln!(f, "{:label$}  │ {row} │", "")?;
+        write!(f, "{:label$}  └{bar}┘", "")
+    }
+}
+
+/// Device naming registry.
+///
+/// Devices are named in order of first appearance.
+struct Names<'a, Idx, V>(Vec<&'a Dynamic<Idx, V>>)
+where
+    Idx: Value,
+    V: Value;
+
+impl<'a, Idx, V> Names<'a, Idx, V>
+where
+    Idx: Value,
+    V: Value,
+{
+    fn new(regions: &'a [Region<Idx, V>]) -> Self {
+        let mut names: Vec<&Dynamic<Idx, V>> = Vec::new();
+        for region in regions {
+            if !names.contains(&&region.dev) {
+                names.push(&region.dev);
+            }
+        }
+        Self(names)
+    }
+
+    fn id(&self, dev: &Dynamic<Idx, V>) -> usize {
+        self.0.iter().position(|&it| it == dev).unwrap_or_default()
+    }
+
+    fn name(&self, region: &Region<Idx, V>) -> String {
+        region
+            .label
+            .clone()
+            .unwrap_or_else(|| format!("D{}", self.id(&region.dev)))
+    }
+}
+
+/// Converts an index into a scalar for display.
+fn num<Idx: Value>(idx: Idx) -> u128 {
+    idx.to_u128().unwrap_or_default()
+}
+
+/// Formats an index as hexadecimal.
+fn hex<Idx: Value>(idx: Idx) -> String {
+    let width = 2 + 2 * std::mem::size_of::<Idx>();
+    idx.to_u128()
+        .map_or_else(|| format!("{idx:?}"), |idx| format!("{idx:#0width$x}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::{Bus, Mux};
+    use crate::dev::Device;
+    use crate::mem::Ram;
+
+    fn setup() -> Bus<u16, u8> {
+        let mut bus = Bus::new();
+        bus.map(0x00..=0x3f, Ram::from(&[0; 0x40]).to_dynamic());
+        bus.map(0x10..=0x1f, Ram::from(&[0; 0x10]).to_dynamic());
+        bus
+    }
+
+    #[test]
+    fn table_works() {
+        let bus = setup();
+        let table = Table::new(&bus.regions()).to_string();
+        assert_eq!(
+            table,
+            [
+                "START    END      PRIO   PERM   DEVICE",
+                "0x0000 - 0x003f      1   rwx    D0",
+                "0x0010 - 0x001f      0   rwx    D1",
+                "",
+            ]
+            .join("\n"),
+        );
+    }
+
+    #[test]
+    fn table_label_works() {
+        let mut bus = setup();
+        let handle = bus.regions()[1].handle;
+        bus.label(handle, Some(String::from("hram")));
+        let table = Table::new(&bus.regions()).to_string();
+        assert!(table.lines().nth(2).unwrap().ends_with("   hram"));
+    }
+
+    #[test]
+    fn diagram_works() {
+        let bus = setup();
+        let diagram = Diagram::new(&bus.regions()).width(16).to_string();
+        assert_eq!(
+            diagram,
+            [
+                "    ┌──────────────────┐",
+                "D0: │ aaaaaaaaaaaaaaaa │",
+                "D1: │     bbbb         │",
+                "    ├──────────────────┤",
+                "    │ aaaabbbbaaaaaaaa │",
+                "    └──────────────────┘",
+            ]
+            .join("\n"),
+        );
+    }
+}
@@ -0,0 +1,230 @@
+//! Access tracing.
+//!
+//! # Usage
+//!
+//! A [`Bus`](super::Bus) can be instrumented with [watchpoints](Watch) over
+//! ranges of its address space. Whenever a watched index is successfully
+//! accessed, the watchpoint's [`Action`] is performed on the resulting
+//! [`Event`], which records the [context](crate::Context) of the access:
+//!
+//! ```
+//! use remus::bus::trace::Action;
+//! use remus::bus::{Bus, Mux};
+//! use remus::dev::Device;
+//! use remus::mem::Ram;
+//! use remus::{Access, Address};
+//!
+//! let mut bus = Bus::<u16, u8>::new();
+//! bus.map(0x0000..=0x00ff, Ram::<u8, 0x100>::new().to_dynamic());
+//! bus.watch(0x0010..=0x001f, Access::Write, Action::Log);
+//! bus.set_tracing(true);
+//!
+//! bus.write(0x0012, 0xaa);
+//! let log = bus.take_log();
+//! assert_eq!(log.len(), 1);
+//! assert_eq!(log[0].value, 0xaa);
+//! assert_eq!(log[0].ctx.access, Access::Write);
+//! ```
+//!
+//! Tracing can be toggled at runtime without modifying the bus's mappings. A
+//! bus that has never been instrumented incurs no tracing overhead.
+//!
+//! Callbacks are invoked once the tracer has been released, and so may
+//! themselves access the bus. However, a callback is not re-entered by the
+//! events of its own accesses.
+
+use std::cell::RefCell;
+use std::fmt::Debug;
+use std::rc::Rc;
+
+use super::Range;
+use crate::arch::{Access, Context, Value};
+
+/// Traced access.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Event<Idx, V>
+where
+    Idx: Value,
+    V: Value,
+{
+    /// Accessed index.
+    pub index: Idx,
+    /// Context of the access, identifying its kind and initiator.
+    pub ctx: Context,
+    /// Value read or written.
+    pub value: V,
+}
+
+/// Watchpoint action.
+pub enum Action<Idx, V>
+where
+    Idx: Value,
+    V: Value,
+{
+    /// Record the event into the bus's log.
+    Log,
+    /// Invoke the callback with the event.
+    Call(Callback<Idx, V>),
+}
+
+/// Watchpoint callback.
+pub type Callback<Idx, V> = Box<dyn FnMut(&Event<Idx, V>)>;
+
+impl<Idx, V> Debug for Action<Idx, V>
+where
+    Idx: Value,
+    V: Value,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Log => write!(f, "Log"),
+            Self::Call(_) => write!(f, "Call"),
+        }
+    }
+}
+
+/// Watchpoint handle.
+///
+/// Uniquely identifies a watchpoint within a [`Bus`](super::Bus).
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Watch(usize);
+
+/// Access tracer.
+#[derive(Debug)]
+pub(super) struct Tracer<Idx, V>
+where
+    Idx: Value,
+    V: Value,
+{
+    on: bool,
+    next: usize,
+    list: Vec<Point<Idx, V>>,
+    log: Vec<Event<Idx, V>>,
+}
+
+impl<Idx, V> Tracer<Idx, V>
+where
+    Idx: Value,
+    V: Value,
+{
+    /// Checks if tracing is enabled.
+    pub(super) fn enabled(&self) -> bool {
+        self.on
+    }
+
+    /// Enables or disables tracing.
+    pub(super) fn enable(&mut self, on: bool) {
+        self.on = on;
+    }
+
+    /// Registers a watchpoint.
+    pub(super) fn watch(
+        &mut self,
+        range: Range<Idx>,
+        access: Access,
+        action: Action<Idx, V>,
+    ) -> Watch {
+        let id = Watch(self.next);
+        self.next += 1;
+        self.list.push(Point {
+            id,
+            range,
+            access,
+            hook: match action {
+                Action::Log => Hook::Log,
+                Action::Call(f) => Hook::Call(Rc::new(RefCell::new(f))),
+            },
+        });
+        id
+    }
+
+    /// Removes a watchpoint.
+    pub(super) fn unwatch(&mut self, id: Watch) -> bool {
+        let len = self.list.len();
+        self.list.retain(|it| it.id != id);
+        self.list.len() != len
+    }
+
+    /// Takes all recorded events.
+    pub(super) fn take(&mut self) -> Vec<Event<Idx, V>> {
+        std::mem::take(&mut self.log)
+    }
+
+    /// Records the event for all logging watchpoints it triggers, returning
+    /// the callbacks of all others.
+    ///
+    /// Callbacks are returned rather than invoked so that they may be called
+    /// once the tracer is no longer borrowed.
+    pub(super) fn fire(&mut self, event: Event<Idx, V>) -> Vec<Rc<RefCell<Callback<Idx, V>>>> {
+        let mut calls = Vec::new();
+        if !self.on {
+            return calls;
+        }
+        let mut log = false;
+        for point in &self.list {
+            if point.access != event.ctx.access || !point.range.contains(&event.index) {
+                continue;
+            }
+            match &point.hook {
+                Hook::Log => log = true,
+                Hook::Call(f) => calls.push(f.clone()),
+            }
+        }
+        // Record events at most once
+        if log {
+            self.log.push(event);
+        }
+        calls
+    }
+}
+
+impl<Idx, V> Default for Tracer<Idx, V>
+where
+    Idx: Value,
+    V: Value,
+{
+    fn default() -> Self {
+        Self {
+            on: false,
+            next: 0,
+            list: Vec::new(),
+            log: Vec::new(),
+        }
+    }
+}
+
+/// Registered watchpoint.
+#[derive(Debug)]
+struct Point<Idx, V>
+where
+    Idx: Value,
+    V: Value,
+{
+    id: Watch,
+    range: Range<Idx>,
+    access: Access,
+    hook: Hook<Idx, V>,
+}
+
+/// Registered watchpoint action.
+enum Hook<Idx, V>
+where
+    Idx: Value,
+    V: Value,
+{
+    Log,
+    Call(Rc<RefCell<Callback<Idx, V>>>),
+}
+
+impl<Idx, V> Debug for Hook<Idx, V>
+where
+    Idx: Value,
+    V: Value,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Log => write!(f, "Log"),
+            Self::Call(_) => write!(f, "Call"),
+        }
+    }
+}
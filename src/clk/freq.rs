@@ -0,0 +1,225 @@
+use std::fmt::Display;
+use std::num::{NonZeroU32, NonZeroU64};
+use std::time::Duration;
+
+use num::integer::gcd;
+use thiserror::Error;
+
+/// Nanoseconds per second.
+pub(super) const NANOS: u128 = 1_000_000_000;
+
+/// Constructs a [`Duration`] from a number of nanoseconds, saturating at
+/// [`Duration::MAX`].
+pub(super) fn nanos(nanos: u128) -> Duration {
+    let secs = u64::try_from(nanos / NANOS).unwrap_or(u64::MAX);
+    #[allow(clippy::cast_possible_truncation)]
+    Duration::new(secs, (nanos % NANOS) as u32)
+}
+
+/// Clock frequency.
+///
+/// Represented exactly as a rational number of hertz, allowing for
+/// frequencies which are not a whole number of hertz (such as the NTSC master
+/// clock of 236.25/11 MHz), as well as those beyond the range of a `u32`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Freq {
+    num: u64,
+    den: u64,
+}
+
+impl Freq {
+    /// Constructs a `Freq` of a whole number of hertz.
+    ///
+    /// See [`Freq::try_from`] for a non-panicking alternative.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `hz` is zero.
+    #[must_use]
+    pub fn hz(hz: u64) -> Self {
+        Self::ratio(hz, 1)
+    }
+
+    /// Constructs a `Freq` of `num / den` hertz.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either `num` or `den` is zero.
+    #[must_use]
+    pub fn ratio(num: u64, den: u64) -> Self {
+        assert!(num != 0, "frequency must be non-zero");
+        assert!(den != 0, "frequency denominator must be non-zero");
+        let div = gcd(num, den);
+        Self {
+            num: num / div,
+            den: den / div,
+        }
+    }
+
+    /// Constructs the `Freq` of a period.
+    ///
+    /// See [`Freq::try_from`] for a non-panicking alternative.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the period is shorter than a nanosecond, or longer than
+    /// [`u64::MAX`] nanoseconds.
+    #[must_use]
+    pub fn from_period(period: Duration) -> Self {
+        Self::try_from(period).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Returns the numerator of the frequency, in hertz.
+    #[must_use]
+    pub fn num(self) -> u64 {
+        self.num
+    }
+
+    /// Returns the denominator of the frequency.
+    #[must_use]
+    pub fn den(self) -> u64 {
+        self.den
+    }
+
+    /// Returns the frequency in whole hertz, rounded to the nearest.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn to_hz(self) -> u64 {
+        ((u128::from(self.num) + u128::from(self.den / 2)) / u128::from(self.den)) as u64
+    }
+
+    /// Returns the frequency in hertz as a floating-point number.
+    #[allow(clippy::cast_precision_loss)]
+    #[must_use]
+    pub fn to_f64(self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+
+    /// Returns the period of the frequency, rounded to the nearest
+    /// nanosecond.
+    #[must_use]
+    pub fn period(self) -> Duration {
+        let num = u128::from(self.num);
+        let nanos = (u128::from(self.den) * NANOS + num / 2) / num;
+        self::nanos(nanos)
+    }
+}
+
+impl Display for Freq {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.den == 1 {
+            write!(f, "{} Hz", self.num)
+        } else {
+            write!(f, "{}/{} Hz", self.num, self.den)
+        }
+    }
+}
+
+impl From<NonZeroU32> for Freq {
+    fn from(hz: NonZeroU32) -> Self {
+        Self::hz(u64::from(hz.get()))
+    }
+}
+
+impl From<NonZeroU64> for Freq {
+    fn from(hz: NonZeroU64) -> Self {
+        Self::hz(hz.get())
+    }
+}
+
+impl TryFrom<u32> for Freq {
+    type Error = Error;
+
+    /// Converts a whole number of hertz into a frequency.
+    fn try_from(hz: u32) -> Result<Self, Self::Error> {
+        Self::try_from(u64::from(hz))
+    }
+}
+
+impl TryFrom<u64> for Freq {
+    type Error = Error;
+
+    /// Converts a whole number of hertz into a frequency.
+    fn try_from(hz: u64) -> Result<Self, Self::Error> {
+        if hz == 0 {
+            return Err(Error::Zero);
+        }
+        Ok(Self::hz(hz))
+    }
+}
+
+impl TryFrom<Duration> for Freq {
+    type Error = Error;
+
+    /// Converts a period into its frequency.
+    fn try_from(period: Duration) -> Result<Self, Self::Error> {
+        let nanos = u64::try_from(period.as_nanos())
+            .ok()
+            .filter(|&nanos| nanos != 0)
+            .ok_or(Error::Period(period))?;
+        #[allow(clippy::cast_possible_truncation)]
+        Ok(Self::ratio(NANOS as u64, nanos))
+    }
+}
+
+/// A type specifying general categories of [`Freq`] error.
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum Error {
+    #[error("frequency must be non-zero")]
+    Zero,
+    #[error("period out of range: {0:?}")]
+    Period(Duration),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ratio_works() {
+        let freq = Freq::ratio(236_250_000, 11);
+        assert_eq!(freq.to_hz(), 21_477_273);
+        assert_eq!(freq.to_string(), "236250000/11 Hz");
+        let freq = Freq::ratio(8_388_608, 2);
+        assert_eq!((freq.num(), freq.den()), (4_194_304, 1));
+        assert_eq!(freq.to_string(), "4194304 Hz");
+    }
+
+    #[test]
+    fn period_works() {
+        assert_eq!(Freq::hz(1_000).period(), Duration::from_millis(1));
+        assert_eq!(Freq::hz(3).period(), Duration::from_nanos(333_333_333));
+        assert_eq!(
+            Freq::from_period(Duration::from_micros(4)),
+            Freq::hz(250_000)
+        );
+        assert_eq!(Freq::from_period(Duration::from_secs(3)), Freq::ratio(1, 3));
+        // Frequencies beyond `u32` are supported
+        assert_eq!(Freq::hz(5_000_000_000).period(), Duration::from_nanos(0));
+    }
+
+    #[test]
+    fn try_from_works() {
+        assert_eq!(
+            Freq::try_from(5_000_000_000u64),
+            Ok(Freq::hz(5_000_000_000))
+        );
+        assert_eq!(Freq::try_from(0u64), Err(Error::Zero));
+        assert_eq!(
+            Freq::try_from(Duration::ZERO),
+            Err(Error::Period(Duration::ZERO))
+        );
+        assert_eq!(
+            Freq::try_from(Duration::MAX),
+            Err(Error::Period(Duration::MAX))
+        );
+        assert_eq!(Freq::try_from(0u32), Err(Error::Zero));
+        assert_eq!(Freq::from(NonZeroU32::new(1_000).unwrap()), Freq::hz(1_000));
+    }
+
+    #[test]
+    #[should_panic(expected = "frequency must be non-zero")]
+    fn zero_panics() {
+        let _ = Freq::hz(0);
+    }
+}
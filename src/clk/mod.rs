@@ -0,0 +1,699 @@
+//! Clock signals.
+//!
+//! # Usage
+//!
+//! A clock [`Source`] yields ticks as an [`Iterator`]. Two sources are
+//! provided:
+//!
+//! - [`Clock`]: ticks in accordance with elapsed real time, for interactive
+//!   frontends.
+//! - [`Virtual`]: ticks deterministically in virtual time, for headless or
+//!   test runs.
+//!
+//! Code generic over `Source` can have either swapped in:
+//!
+//! ```
+//! use remus::clk::{Freq, Source, Virtual};
+//!
+//! fn run(clk: &mut impl Source, cycles: usize) -> usize {
+//!     clk.take(cycles).count()
+//! }
+//!
+//! let mut clk = Virtual::with_freq(Freq::hz(4_194_304));
+//! assert_eq!(run(&mut clk, 1000), 1000);
+//! ```
+//!
+//! # Frequencies
+//!
+//! Frequencies are represented exactly as a rational [`Freq`], such that
+//! clocks which are not a whole number of hertz (or exceed a `u32`) tick
+//! without drift:
+//!
+//! ```
+//! use remus::clk::{Freq, Virtual};
+//!
+//! // NTSC master clock (236.25/11 MHz)
+//! let mut clk = Virtual::with_freq(Freq::ratio(236_250_000, 11)).manual();
+//! clk.advance(236_250_000);
+//! clk.batch();
+//! assert_eq!(clk.elapsed().as_secs(), 11);
+//! ```
+
+use std::num::NonZeroU64;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use num::integer::gcd;
+
+use self::freq::{nanos, NANOS};
+
+/// Minimum duration slept by the run-thread between wakeups.
+///
+/// Prevents the run-thread from spinning at high frequencies, where a single
+/// period is shorter than the host can meaningfully sleep.
+const MIN_SLEEP: Duration = Duration::from_millis(1);
+
+mod freq;
+mod virt;
+
+pub use self::freq::{Error, Freq};
+pub use self::virt::Virtual;
+
+/// Clock signal generator.
+///
+/// An [`Iterator`] that ensures values are yielded on average[^1] according to
+/// the [elapsed real time]. `Clock` internally handles the ilogic of keeping
+/// track of the "ticks" (rising edges) of the clock signal.
+///
+/// [^1]: As `Clock` internally uses the host machine's [`sleep`](thread::sleep)
+///       functionality, the host OS may elect to sleep for longer than the
+///       specified duration. To combat this, upon waking from sleep the `Clock`
+///       will check how long it has been sleeping, and tick accordingly to make
+///       up missed cycles.
+///
+/// # Batching
+///
+/// Rather than yielding each tick individually, a `Clock` can also deliver the
+/// number of ticks elapsed per wakeup as a [batch](Clock::batch). This is
+/// significantly cheaper at high (e.g. MHz-range) frequencies. To avoid
+/// spiralling after a host stall, the number of ticks delivered at once can be
+/// bounded with [`Clock::set_catch_up`].
+///
+/// The run-thread wakes at most once per millisecond. At frequencies whose
+/// period is shorter than this, ticks are clocked-in in bursts, averaging out
+/// to the requested frequency.
+///
+/// The run-thread is owned by the `Clock`, and is stopped and joined when the
+/// clock is [stopped](Clock::stop) or dropped. While paused, the run-thread is
+/// parked until resumed.
+///
+/// # Note
+///
+/// In accordance with [`Source`], iteration ends while the clock is paused
+/// (once any ticks already clocked-in have been delivered), but continues once
+/// resumed. Only a running clock blocks awaiting its next tick.
+///
+/// [elapsed real time]: https://en.wikipedia.org/wiki/Elapsed_real_time
+#[derive(Debug)]
+pub struct Clock {
+    ctl: Arc<Control>,
+    rx: Receiver<u64>,
+    th: Option<JoinHandle<()>>,
+    pending: u64,
+}
+
+impl Clock {
+    /// Constructs a `Clock` that ticks at the provided frequency.
+    #[must_use]
+    pub fn with_freq(freq: impl Into<Freq>) -> Self {
+        // Start the run-thread
+        Self::start(freq.into())
+    }
+
+    /// Constructs a `Clock` whose ticks last the provided duration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the period is shorter than a nanosecond.
+    #[must_use]
+    pub fn with_period(period: Duration) -> Self {
+        // Calculate this duration's corresponding frequency.
+        Self::with_freq(Freq::from_period(period))
+    }
+
+    /// Spins up a run-thread for execution.
+    fn start(freq: Freq) -> Self {
+        // Create a receiver/sender pair for transmitting clock ticks
+        let (tx, rx) = mpsc::channel();
+        // Create the shared run-thread control
+        let ctl = Arc::new(Control::new(freq));
+
+        // Spin up the run-thread
+        let th = {
+            let ctl = ctl.clone();
+            thread::spawn(move || {
+                Self::run(&ctl, &tx);
+            })
+        };
+
+        // Return the constructed clock
+        Clock {
+            ctl,
+            rx,
+            th: Some(th),
+            pending: 0,
+        }
+    }
+
+    /// Gets this [`Clock`]'s period, rounded to the nearest nanosecond.
+    #[must_use]
+    pub fn period(&self) -> Duration {
+        self.freq().period()
+    }
+
+    /// Sets this [`Clock`]'s period.
+    ///
+    /// See [`Clock::set_freq`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the period is shorter than a nanosecond.
+    pub fn set_period(&mut self, period: Duration) {
+        self.set_freq(Freq::from_period(period));
+    }
+
+    /// Gets this [`Clock`]'s frequency.
+    #[must_use]
+    pub fn freq(&self) -> Freq {
+        self.ctl.lock().freq
+    }
+
+    /// Sets this [`Clock`]'s frequency.
+    ///
+    /// Takes effect immediately, even while running. Progress towards the
+    /// next tick is preserved, such that no ticks are skipped or repeated.
+    pub fn set_freq(&mut self, freq: impl Into<Freq>) {
+        let freq = freq.into();
+        self.ctl.update(|it| it.freq = freq);
+    }
+
+    /// Gets this [`Clock`]'s speed multiplier.
+    #[must_use]
+    pub fn speed(&self) -> f64 {
+        self.ctl.lock().speed
+    }
+
+    /// Sets this [`Clock`]'s speed multiplier.
+    ///
+    /// Ticks are delivered `speed` times as often as the clock's nominal
+    /// frequency, allowing for fast-forward (`speed > 1.0`) and slow-motion
+    /// (`speed < 1.0`). The clock's [period](Clock::period) and
+    /// [frequency](Clock::freq) are unaffected.
+    ///
+    /// The multiplier is applied with a precision of 2<sup>-20</sup>.
+    ///
+    /// See [`Clock::set_freq`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `speed` is not a positive, finite number.
+    pub fn set_speed(&mut self, speed: f64) {
+        assert!(
+            speed.is_finite() && speed > 0.0,
+            "clock speed must be positive and finite: {speed}"
+        );
+        self.ctl.update(|it| it.speed = speed);
+    }
+
+    /// Gets the maximum number of ticks delivered at once, if bounded.
+    #[must_use]
+    pub fn catch_up(&self) -> Option<NonZeroU64> {
+        match self.ctl.max.load(Ordering::Relaxed) {
+            u64::MAX => None,
+            max => NonZeroU64::new(max),
+        }
+    }
+
+    /// Bounds the number of ticks delivered at once.
+    ///
+    /// Should ticks accumulate beyond this maximum (e.g. after the host
+    /// stalls, or the consumer falls behind), the excess is dropped rather
+    /// than caught up on.
+    pub fn set_catch_up(&mut self, max: Option<NonZeroU64>) {
+        self.ctl
+            .max
+            .store(max.map_or(u64::MAX, NonZeroU64::get), Ordering::Relaxed);
+    }
+
+    /// Yields all elapsed ticks, blocking until at least one is available.
+    ///
+    /// Returns `None` without blocking while the clock is paused, and once
+    /// the clock has been stopped, after all ticks have been delivered.
+    pub fn batch(&mut self) -> Option<u64> {
+        let mut cycles = std::mem::take(&mut self.pending);
+        if cycles == 0 {
+            let state = self.ctl.lock().state;
+            cycles = match state {
+                // Only deliver ticks already clocked-in
+                State::Pause => self.rx.try_recv().ok()?,
+                State::Run | State::Stop => self.rx.recv().ok()?,
+            };
+        }
+        // Collect any other outstanding cycles
+        while let Ok(more) = self.rx.try_recv() {
+            cycles = cycles.saturating_add(more);
+        }
+        Some(cycles.min(self.ctl.max.load(Ordering::Relaxed)))
+    }
+
+    /// Pauses the clock, preventing iterations from progressing.
+    ///
+    /// # Note
+    ///
+    /// Does nothing if the clock is already paused. Upon being paused, cycles
+    /// already clocked-in by the run-thread will still run, after which
+    /// iteration ends until resumed.
+    pub fn pause(&mut self) {
+        self.ctl.set(State::Pause);
+    }
+
+    /// Resumes the clock, iterating at the previous frequency.
+    ///
+    /// # Note
+    ///
+    /// Does nothing if the clock is already running.
+    pub fn resume(&mut self) {
+        self.ctl.set(State::Run);
+    }
+
+    /// Stops the clock, joining its run-thread.
+    ///
+    /// Once stopped, the clock cannot be resumed, and iteration ends after any
+    /// cycles already clocked-in.
+    ///
+    /// # Note
+    ///
+    /// Does nothing if the clock is already stopped.
+    pub fn stop(&mut self) {
+        self.ctl.set(State::Stop);
+        if let Some(th) = self.th.take() {
+            // NOTE: The run-thread never panics, so there is no error to
+            //       propagate.
+            let _ = th.join();
+        }
+    }
+
+    /// Checks if the clock has been stopped.
+    #[must_use]
+    pub fn stopped(&self) -> bool {
+        self.th.is_none()
+    }
+
+    /// Main function of a run-thread.
+    ///
+    /// Continually sends clock ticks at the provided frequency, until either
+    /// stopped or the receiver hangs up.
+    ///
+    /// # Note
+    ///
+    /// Progress is tracked exactly in integer units of `1 / (den * NANOS)` of
+    /// a tick, measured against a continuous timeline. As such, no time is
+    /// lost to rounding or between wakeups, and the clock does not drift.
+    fn run(ctl: &Control, tx: &Sender<u64>) {
+        // Keep track of fractional missed cycles
+        let mut acc: u128 = 0;
+        let (mut num, mut den) = ctl.lock().rate();
+        // Keep track of the last accounted-for instant
+        let mut last = Instant::now();
+
+        loop {
+            // Park until resumed, discounting any time spent parked
+            let mut parked = false;
+            let it = ctl.wait(|it| {
+                parked |= it.state == State::Pause;
+                it.state == State::Pause
+            });
+            if it.state == State::Stop {
+                return;
+            }
+            if parked {
+                last = Instant::now();
+            }
+            // Rescale progress towards the next cycle upon a change in rate
+            let (next, per) = it.rate();
+            if (next, per) != (num, den) {
+                acc = (acc.saturating_mul(per) / den).min(per * NANOS - 1);
+                (num, den) = (next, per);
+            }
+            let unit = den * NANOS;
+            // Sleep until the next cycle, waking early if interrupted
+            // NOTE: Due to OS scheduling, the sleep may last longer than the
+            //       specified duration. Because of this, we must record how
+            //       many cycles were missed.
+            let dur = nanos((unit - acc).div_ceil(num)).max(MIN_SLEEP);
+            let it = ctl.sleep(&it, dur);
+            if it.state == State::Stop {
+                return;
+            }
+            // Calculate how many cycles were slept through
+            let cycles = {
+                let now = Instant::now();
+                // Get elapsed progress (with remainder)
+                let prog = (now - last)
+                    .as_nanos()
+                    .saturating_mul(num)
+                    .saturating_add(acc);
+                last = now;
+                // Calculate elapsed cycle remainder
+                acc = prog % unit;
+                // Calculate elapsed complete cycles
+                u64::try_from(prog / unit).unwrap_or(u64::MAX)
+            };
+            // Drop any cycles beyond the maximum catch-up
+            let max = ctl.max.load(Ordering::Relaxed);
+            if cycles > max {
+                acc = 0;
+            }
+            let cycles = cycles.min(max);
+            // Clock in elapsed cycles. Run until failure (caused by the
+            // receiver hanging up).
+            if cycles > 0 && tx.send(cycles).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+impl Source for Clock {
+    fn freq(&self) -> Freq {
+        self.freq()
+    }
+
+    fn pause(&mut self) {
+        self.pause();
+    }
+
+    fn resume(&mut self) {
+        self.resume();
+    }
+
+    fn batch(&mut self) -> Option<u64> {
+        self.batch()
+    }
+}
+
+impl Drop for Clock {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl Iterator for Clock {
+    type Item = ();
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pending == 0 {
+            self.pending = self.batch()?;
+        }
+        self.pending -= 1;
+        Some(())
+    }
+}
+
+/// Clock signal source.
+///
+/// Yields a value for each tick of the clock signal.
+///
+/// # Contract
+///
+/// A source yields `None` (from both [`Iterator::next`] and
+/// [`Source::batch`]) whenever it has no ticks available and cannot produce
+/// any without outside intervention, such as while [paused](Source::pause), or
+/// once exhausted. This does not fuse the source: iteration continues once it
+/// is [resumed](Source::resume) (or otherwise replenished).
+///
+/// A running source may block until its next tick is due, but a paused source
+/// must never block.
+pub trait Source: Iterator<Item = ()> {
+    /// Gets the source's frequency.
+    fn freq(&self) -> Freq;
+
+    /// Gets the source's period, rounded to the nearest nanosecond.
+    fn period(&self) -> Duration {
+        self.freq().period()
+    }
+
+    /// Pauses the source, preventing iterations from progressing.
+    ///
+    /// While paused, iteration yields `None`.
+    fn pause(&mut self);
+
+    /// Resumes the source, iterating at the previous frequency.
+    fn resume(&mut self);
+
+    /// Yields the number of ticks currently available as a single batch.
+    ///
+    /// # Note
+    ///
+    /// The provided implementation yields a single tick per batch.
+    fn batch(&mut self) -> Option<u64> {
+        self.next().map(|()| 1)
+    }
+
+    /// Returns an iterator over batches of ticks.
+    fn batches(&mut self) -> Batches<'_, Self>
+    where
+        Self: Sized,
+    {
+        Batches(self)
+    }
+}
+
+/// Batched clock signal.
+///
+/// An [`Iterator`] yielding batches of ticks from a [`Source`], as returned by
+/// [`Source::batches`].
+#[derive(Debug)]
+pub struct Batches<'a, S: Source>(&'a mut S);
+
+impl<S: Source> Iterator for Batches<'_, S> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.batch()
+    }
+}
+
+/// Run-thread state.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+enum State {
+    /// Clocking in cycles.
+    #[default]
+    Run,
+    /// Parked until resumed.
+    Pause,
+    /// Exiting.
+    Stop,
+}
+
+/// Run-thread settings.
+#[derive(Clone, Copy, Debug)]
+struct Settings {
+    /// Run-thread state.
+    state: State,
+    /// Nominal frequency.
+    freq: Freq,
+    /// Speed multiplier.
+    speed: f64,
+    /// Counts changes to the rate.
+    epoch: u64,
+}
+
+impl Settings {
+    /// Precision of the speed multiplier.
+    const SCALE: u32 = 1 << 20;
+
+    /// Returns the effective frequency, as a reduced ratio of hertz.
+    #[allow(clippy::cast_possible_truncation)]
+    #[allow(clippy::cast_precision_loss)]
+    #[allow(clippy::cast_sign_loss)]
+    fn rate(&self) -> (u128, u128) {
+        let speed = (self.speed * f64::from(Self::SCALE)).round().max(1.0) as u128;
+        let num = u128::from(self.freq.num()).saturating_mul(speed);
+        let den = u128::from(self.freq.den()) * u128::from(Self::SCALE);
+        let div = gcd(num, den);
+        (num / div, den / div)
+    }
+}
+
+/// Run-thread control.
+///
+/// Shares the [`Settings`] with the run-thread, waking it upon changes.
+#[derive(Debug)]
+struct Control {
+    it: Mutex<Settings>,
+    cvar: Condvar,
+    max: AtomicU64,
+}
+
+impl Control {
+    /// Constructs a new `Control` for a running clock.
+    fn new(freq: Freq) -> Self {
+        Self {
+            it: Mutex::new(Settings {
+                state: State::default(),
+                freq,
+                speed: 1.0,
+                epoch: 0,
+            }),
+            cvar: Condvar::default(),
+            max: AtomicU64::new(u64::MAX),
+        }
+    }
+
+    /// Locks the settings.
+    ///
+    /// As the settings are always valid, a poisoned lock is recovered.
+    fn lock(&self) -> MutexGuard<'_, Settings> {
+        self.it.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Sets the state, waking the run-thread.
+    ///
+    /// A stopped state is never left.
+    fn set(&self, next: State) {
+        let mut it = self.lock();
+        if it.state != State::Stop {
+            it.state = next;
+        }
+        self.cvar.notify_all();
+    }
+
+    /// Updates the rate, waking the run-thread.
+    fn update(&self, f: impl FnOnce(&mut Settings)) {
+        let mut it = self.lock();
+        f(&mut it);
+        it.epoch += 1;
+        self.cvar.notify_all();
+    }
+
+    /// Blocks while the condition holds, returning the resulting settings.
+    fn wait(&self, mut cond: impl FnMut(&Settings) -> bool) -> Settings {
+        *self
+            .cvar
+            .wait_while(self.lock(), |it| cond(it))
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Sleeps for a duration while the settings are unchanged, returning the
+    /// resulting settings.
+    fn sleep(&self, prev: &Settings, dur: Duration) -> Settings {
+        let (it, _) = self
+            .cvar
+            .wait_timeout_while(self.lock(), dur, |it| {
+                it.state == State::Run && it.epoch == prev.epoch
+            })
+            .unwrap_or_else(PoisonError::into_inner);
+        *it
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iterator_works() {
+        let clk = Clock::with_freq(Freq::hz(10_000));
+        assert_eq!(clk.take(100).count(), 100);
+    }
+
+    #[test]
+    fn stop_works() {
+        let mut clk = Clock::with_period(Duration::from_secs(100));
+        let now = Instant::now();
+        clk.stop();
+        // Stopping interrupts the run-thread's sleep
+        assert!(now.elapsed() < Duration::from_secs(10));
+        assert!(clk.stopped());
+        assert_eq!(clk.next(), None);
+        // Stopped clocks cannot be resumed
+        clk.resume();
+        assert_eq!(clk.next(), None);
+    }
+
+    #[test]
+    fn pause_resume_works() {
+        let mut clk = Clock::with_freq(Freq::hz(10_000));
+        clk.pause();
+        // Drain cycles clocked-in before pausing
+        thread::sleep(Duration::from_millis(10));
+        while clk.rx.try_recv().is_ok() {}
+        thread::sleep(Duration::from_millis(10));
+        assert!(clk.rx.try_recv().is_err());
+        // Paused clocks do not block
+        assert_eq!(clk.next(), None);
+        assert_eq!(clk.batch(), None);
+        clk.resume();
+        assert_eq!(clk.next(), Some(()));
+    }
+
+    #[test]
+    fn batch_works() {
+        let mut clk = Clock::with_freq(Freq::hz(1_000_000));
+        thread::sleep(Duration::from_millis(20));
+        // Many ticks are delivered at once
+        let batch = clk.batch().unwrap();
+        assert!(batch > 1);
+        // Ticks are not duplicated between batches and iteration
+        clk.pending = 3;
+        assert_eq!(clk.by_ref().take(2).count(), 2);
+        assert!(clk.batch().unwrap() >= 1);
+        assert_eq!(clk.batches().take(3).count(), 3);
+    }
+
+    #[test]
+    fn catch_up_works() {
+        let mut clk = Clock::with_freq(Freq::hz(1_000_000));
+        assert_eq!(clk.catch_up(), None);
+        clk.set_catch_up(NonZeroU64::new(100));
+        assert_eq!(clk.catch_up(), NonZeroU64::new(100));
+        // Simulate a stalled consumer
+        thread::sleep(Duration::from_millis(20));
+        assert!(clk.batch().unwrap() <= 100);
+    }
+
+    #[test]
+    fn set_period_works() {
+        let mut clk = Clock::with_period(Duration::from_secs(100));
+        clk.set_freq(Freq::hz(1_000));
+        assert_eq!(clk.freq(), Freq::hz(1_000));
+        assert_eq!(clk.period(), Duration::from_millis(1));
+        // The run-thread is woken at the new rate
+        let now = Instant::now();
+        assert_eq!(clk.next(), Some(()));
+        assert!(now.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn exact_freq_works() {
+        // Frequencies beyond `u32` are supported
+        let clk = Clock::with_freq(Freq::hz(5_000_000_000));
+        assert_eq!(clk.freq(), Freq::hz(5_000_000_000));
+        assert_eq!(clk.take(1_000).count(), 1_000);
+        // As are fractional frequencies
+        let mut clk = Clock::with_freq(Freq::ratio(236_250_000, 11));
+        assert_eq!(clk.freq().to_hz(), 21_477_273);
+        assert!(clk.batch().unwrap() > 0);
+    }
+
+    #[test]
+    fn set_speed_works() {
+        let mut clk = Clock::with_period(Duration::from_secs(100));
+        clk.set_speed(1e5);
+        assert!((clk.speed() - 1e5).abs() < f64::EPSILON);
+        assert_eq!(clk.period(), Duration::from_secs(100));
+        let now = Instant::now();
+        assert_eq!(clk.next(), Some(()));
+        assert!(now.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    #[should_panic(expected = "clock speed must be positive and finite")]
+    fn set_speed_panics() {
+        let mut clk = Clock::with_freq(Freq::hz(1));
+        clk.set_speed(0.0);
+    }
+
+    #[test]
+    fn drop_works() {
+        let clk = Clock::with_freq(Freq::hz(1));
+        let ctl = Arc::downgrade(&clk.ctl);
+        drop(clk);
+        // The run-thread has exited, releasing its control
+        assert!(ctl.upgrade().is_none());
+    }
+}
@@ -0,0 +1,233 @@
+use std::time::Duration;
+
+use super::{nanos, Freq, Source, NANOS};
+
+/// Virtual clock signal generator.
+///
+/// An [`Iterator`] that yields ticks in virtual time, independently of the
+/// host's real time. This makes execution deterministic, and as fast as the
+/// ticks are consumed.
+///
+/// # Manual stepping
+///
+/// By default, a `Virtual` clock is free-running, yielding ticks for as long
+/// as it is iterated. A [manual](Virtual::manual) clock instead only yields
+/// the ticks it has been [advanced](Virtual::advance) by.
+///
+/// # Note
+///
+/// In accordance with [`Source`], iteration ends while the clock is paused, or
+/// has no ticks remaining, but continues once resumed or advanced. As virtual
+/// time only progresses when iterated, a `Virtual` clock never blocks.
+#[derive(Clone, Debug)]
+pub struct Virtual {
+    freq: Freq,
+    go: bool,
+    left: Option<u64>,
+    ticks: u64,
+}
+
+impl Virtual {
+    /// Constructs a `Virtual` clock that ticks at the provided frequency.
+    #[must_use]
+    pub fn with_freq(freq: impl Into<Freq>) -> Self {
+        Self {
+            freq: freq.into(),
+            go: true,
+            left: None,
+            ticks: 0,
+        }
+    }
+
+    /// Constructs a `Virtual` clock whose ticks last the provided duration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the period is shorter than a nanosecond.
+    #[must_use]
+    pub fn with_period(period: Duration) -> Self {
+        Self::with_freq(Freq::from_period(period))
+    }
+
+    /// Converts the clock to be manually stepped.
+    #[must_use]
+    pub fn manual(mut self) -> Self {
+        self.left = Some(0);
+        self
+    }
+
+    /// Checks if the clock is manually stepped.
+    #[must_use]
+    pub fn is_manual(&self) -> bool {
+        self.left.is_some()
+    }
+
+    /// Advances a manual clock, allowing it to yield `n` more ticks.
+    ///
+    /// # Note
+    ///
+    /// Does nothing if the clock is free-running.
+    pub fn advance(&mut self, n: u64) {
+        if let Some(left) = &mut self.left {
+            *left = left.saturating_add(n);
+        }
+    }
+
+    /// Gets this [`Virtual`] clock's period, rounded to the nearest
+    /// nanosecond.
+    #[must_use]
+    pub fn period(&self) -> Duration {
+        self.freq.period()
+    }
+
+    /// Gets this [`Virtual`] clock's frequency.
+    #[must_use]
+    pub fn freq(&self) -> Freq {
+        self.freq
+    }
+
+    /// Returns the number of ticks yielded.
+    #[must_use]
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Returns the virtual time elapsed over all yielded ticks.
+    ///
+    /// Computed exactly from the tick count, such that no error accumulates
+    /// over time, then truncated to the nanosecond.
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        let nanos = u128::from(self.ticks) * u128::from(self.freq.den()) * NANOS
+            / u128::from(self.freq.num());
+        self::nanos(nanos)
+    }
+
+    /// Yields all remaining ticks of a manual clock as a single batch.
+    ///
+    /// A free-running clock yields a single tick per batch.
+    pub fn batch(&mut self) -> Option<u64> {
+        if !self.go {
+            return None;
+        }
+        match &mut self.left {
+            Some(left) => {
+                let cycles = std::mem::take(left);
+                self.ticks += cycles;
+                (cycles > 0).then_some(cycles)
+            }
+            None => self.next().map(|()| 1),
+        }
+    }
+
+    /// Pauses the clock, preventing iterations from progressing.
+    ///
+    /// # Note
+    ///
+    /// Does nothing if the clock is already paused.
+    pub fn pause(&mut self) {
+        self.go = false;
+    }
+
+    /// Resumes the clock, iterating at the previous frequency.
+    ///
+    /// # Note
+    ///
+    /// Does nothing if the clock is already running.
+    pub fn resume(&mut self) {
+        self.go = true;
+    }
+}
+
+impl Iterator for Virtual {
+    type Item = ();
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.go {
+            return None;
+        }
+        if let Some(left) = &mut self.left {
+            *left = left.checked_sub(1)?;
+        }
+        self.ticks += 1;
+        Some(())
+    }
+}
+
+impl Source for Virtual {
+    fn freq(&self) -> Freq {
+        self.freq()
+    }
+
+    fn pause(&mut self) {
+        self.pause();
+    }
+
+    fn resume(&mut self) {
+        self.resume();
+    }
+
+    fn batch(&mut self) -> Option<u64> {
+        self.batch()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn free_works() {
+        let mut clk = Virtual::with_freq(Freq::hz(1_000));
+        assert_eq!(clk.by_ref().take(2_500).count(), 2_500);
+        assert_eq!(clk.ticks(), 2_500);
+        assert_eq!(clk.elapsed(), Duration::from_millis(2_500));
+    }
+
+    #[test]
+    fn manual_works() {
+        let mut clk = Virtual::with_period(Duration::from_micros(1)).manual();
+        assert_eq!(clk.next(), None);
+        clk.advance(3);
+        assert_eq!(clk.by_ref().count(), 3);
+        clk.advance(2);
+        assert_eq!(clk.next(), Some(()));
+        assert_eq!(clk.ticks(), 4);
+        assert_eq!(clk.elapsed(), Duration::from_micros(4));
+    }
+
+    #[test]
+    fn batch_works() {
+        let mut clk = Virtual::with_freq(Freq::hz(1_000)).manual();
+        assert_eq!(clk.batch(), None);
+        clk.advance(500);
+        clk.advance(250);
+        assert_eq!(clk.batches().collect::<Vec<_>>(), [750]);
+        assert_eq!(clk.elapsed(), Duration::from_millis(750));
+    }
+
+    #[test]
+    fn exact_works() {
+        // 4.194304 MHz has no whole-nanosecond period
+        let mut clk = Virtual::with_freq(Freq::hz(4_194_304)).manual();
+        assert_eq!(clk.period(), Duration::from_nanos(238));
+        clk.advance(4_194_304 * 3_600);
+        assert_eq!(clk.batch(), Some(4_194_304 * 3_600));
+        assert_eq!(clk.elapsed(), Duration::from_secs(3_600));
+        // Nor does the NTSC master clock
+        let mut clk = Virtual::with_freq(Freq::ratio(236_250_000, 11)).manual();
+        clk.advance(236_250_000 * 360);
+        clk.batch();
+        assert_eq!(clk.elapsed(), Duration::from_secs(66 * 60));
+        assert_eq!(Source::freq(&clk).to_hz(), 21_477_273);
+    }
+
+    #[test]
+    fn pause_resume_works() {
+        let mut clk = Virtual::with_freq(Freq::hz(1_000));
+        clk.pause();
+        assert_eq!(clk.next(), None);
+        clk.resume();
+        assert_eq!(clk.next(), Some(()));
+    }
+}
@@ -7,7 +7,9 @@
 //!
 //! [memory-mapped I/O]: https://en.wikipedia.org/wiki/Memory-mapped_I/O
 
-use crate::arch::{Address, Value};
+use std::ops::RangeInclusive;
+
+use crate::arch::{Access, Address, Context, Value};
 use crate::blk::Block;
 use crate::share::Shared;
 
@@ -23,6 +25,231 @@ where
     Idx: Value,
     V: Value,
 {
+    /// Fallibly reads from the specified address.
+    ///
+    /// Unlike [`TryAddress::try_read`](crate::TryAddress::try_read), errors are
+    /// type-erased, allowing for fallible access through a [`Dynamic`] device.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the read is not successful.
+    ///
+    /// # Note
+    ///
+    /// The provided implementation forwards to [`Address::read`], and so never
+    /// errors.
+    fn try_read_dyn(&self, index: Idx) -> Result<V, Error> {
+        Ok(self.read(index))
+    }
+
+    /// Fallibly writes to the specified address.
+    ///
+    /// Unlike [`TryAddress::try_write`](crate::TryAddress::try_write), errors
+    /// are type-erased, allowing for fallible access through a [`Dynamic`]
+    /// device.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the write is not successful.
+    ///
+    /// # Note
+    ///
+    /// The provided implementation forwards to [`Address::write`], and so never
+    /// errors.
+    fn try_write_dyn(&mut self, index: Idx, value: V) -> Result<(), Error> {
+        self.write(index, value);
+        Ok(())
+    }
+
+    /// Fallibly reads from the specified address within the provided context.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the read is not successful.
+    ///
+    /// # Note
+    ///
+    /// The provided implementation ignores the context, forwarding
+    /// [debug](Access::Debug) reads to [`Device::peek`] and all others to
+    /// [`Device::try_read_dyn`]. Adapters should override this to forward the
+    /// context to their inner devices.
+    fn try_read_ctx(&self, index: Idx, ctx: Context) -> Result<V, Error> {
+        match ctx.access {
+            Access::Debug => Ok(self.peek(index)),
+            _ => self.try_read_dyn(index),
+        }
+    }
+
+    /// Fallibly writes to the specified address within the provided context.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the write is not successful.
+    ///
+    /// # Note
+    ///
+    /// The provided implementation ignores the context, forwarding
+    /// [debug](Access::Debug) writes to [`Device::poke`] and all others to
+    /// [`Device::try_write_dyn`]. Adapters should override this to forward the
+    /// context to their inner devices.
+    fn try_write_ctx(&mut self, index: Idx, value: V, ctx: Context) -> Result<(), Error> {
+        match ctx.access {
+            Access::Debug => {
+                self.poke(index, value);
+                Ok(())
+            }
+            _ => self.try_write_dyn(index, value),
+        }
+    }
+
+    /// Reads from the specified address within the provided context.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the read is not successful.
+    ///
+    /// # Note
+    ///
+    /// The provided implementation forwards to [`Device::try_read_ctx`], which
+    /// should be overridden instead.
+    fn read_ctx(&self, index: Idx, ctx: Context) -> V {
+        self.try_read_ctx(index, ctx)
+            .unwrap_or_else(|err| panic!("`Device::read_ctx`: {err}"))
+    }
+
+    /// Writes to the specified address within the provided context.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the write is not successful.
+    ///
+    /// # Note
+    ///
+    /// The provided implementation forwards to [`Device::try_write_ctx`],
+    /// which should be overridden instead.
+    fn write_ctx(&mut self, index: Idx, value: V, ctx: Context) {
+        self.try_write_ctx(index, value, ctx)
+            .unwrap_or_else(|err| panic!("`Device::write_ctx`: {err}"));
+    }
+
+    /// Fallibly reads a contiguous block starting at the specified address.
+    ///
+    /// # Errors
+    ///
+    /// Errors if any element of the block could not be read, in which case
+    /// the contents of `buf` are unspecified.
+    ///
+    /// # Note
+    ///
+    /// The provided implementation performs a [`Device::try_read_dyn`] per
+    /// element. Implementors should override this where a faster bulk copy is
+    /// possible.
+    fn try_read_block(&self, index: Idx, buf: &mut [V]) -> Result<(), Error> {
+        let mut index = index;
+        for value in buf {
+            *value = self.try_read_dyn(index)?;
+            index = index.wrapping_add(&Idx::one());
+        }
+        Ok(())
+    }
+
+    /// Fallibly writes a contiguous block starting at the specified address.
+    ///
+    /// # Errors
+    ///
+    /// Errors if any element of the block could not be written, in which case
+    /// the elements preceding it may have already been written.
+    ///
+    /// # Note
+    ///
+    /// The provided implementation performs a [`Device::try_write_dyn`] per
+    /// element. Implementors should override this where a faster bulk copy is
+    /// possible.
+    fn try_write_block(&mut self, index: Idx, buf: &[V]) -> Result<(), Error> {
+        let mut index = index;
+        for &value in buf {
+            self.try_write_dyn(index, value)?;
+            index = index.wrapping_add(&Idx::one());
+        }
+        Ok(())
+    }
+
+    /// Reads a contiguous block starting at the specified address.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the read is not successful.
+    ///
+    /// # Note
+    ///
+    /// The provided implementation forwards to [`Device::try_read_block`],
+    /// which should be overridden instead.
+    fn read_block(&self, index: Idx, buf: &mut [V]) {
+        self.try_read_block(index, buf)
+            .unwrap_or_else(|err| panic!("`Device::read_block`: {err}"));
+    }
+
+    /// Writes a contiguous block starting at the specified address.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the write is not successful.
+    ///
+    /// # Note
+    ///
+    /// The provided implementation forwards to [`Device::try_write_block`],
+    /// which should be overridden instead.
+    fn write_block(&mut self, index: Idx, buf: &[V]) {
+        self.try_write_block(index, buf)
+            .unwrap_or_else(|err| panic!("`Device::write_block`: {err}"));
+    }
+
+    /// Returns the ranges of offsets written since last cleaned.
+    ///
+    /// Adjacent ranges are coalesced, and are ordered by offset.
+    ///
+    /// # Note
+    ///
+    /// The provided implementation does not track writes, and so always
+    /// returns an empty list.
+    fn dirty(&self) -> Vec<RangeInclusive<usize>> {
+        Vec::new()
+    }
+
+    /// Marks all offsets as clean.
+    ///
+    /// # Note
+    ///
+    /// The provided implementation does nothing.
+    fn clean(&mut self) {}
+
+    /// Reads from the specified address without side effects.
+    ///
+    /// Unlike [`Address::read`], this must not have any observable side
+    /// effects on emulation, such as clear-on-read registers or bus latches.
+    /// This allows debuggers and memory viewers to inspect a device without
+    /// perturbing it.
+    ///
+    /// # Note
+    ///
+    /// The provided implementation forwards to [`Address::read`]. Implementors
+    /// should override this if reads have side effects.
+    fn peek(&self, index: Idx) -> V {
+        self.read(index)
+    }
+
+    /// Writes to the specified address without side effects.
+    ///
+    /// Pokes may modify state which is otherwise read-only, such as patching
+    /// a ROM.
+    ///
+    /// # Note
+    ///
+    /// The provided implementation forwards to [`Address::write`].
+    fn poke(&mut self, index: Idx, value: V) {
+        self.write(index, value);
+    }
+
     /// Constructs a [`Shared`] device from `self`.
     fn to_shared(self) -> Shared<Self>
     where
@@ -43,6 +270,9 @@ where
 /// Runtime generic shared device.
 pub type Dynamic<Idx, V> = Shared<dyn Device<Idx, V>>;
 
+/// Type-erased [`Device`] error.
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+
 impl<T, Idx, V> From<Shared<T>> for Dynamic<Idx, V>
 where
     T: Device<Idx, V> + 'static,
@@ -65,6 +295,14 @@ mod tests {
             .for_each(|index: usize| assert_eq!(Ram::from(&[0xaau8; 0x100]).read(index), 0xaa));
     }
 
+    #[test]
+    fn read_block_works() {
+        let null = Null::<u8>::with(0xaa);
+        let mut buf = [0; 0x10];
+        null.read_block(0xfff8usize, &mut buf);
+        assert_eq!(buf, [0xaa; 0x10]);
+    }
+
     #[test]
     fn address_write_works() {
         let mut dev = Ram::from(&[0u8; 0x100]);
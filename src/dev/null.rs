@@ -1,8 +1,10 @@
 use thiserror::Error;
 
-use super::Device;
 use crate::arch::{Address, TryAddress, Value};
 use crate::blk::Block;
+use crate::dev::{self, Device};
+#[cfg(feature = "state")]
+use crate::state;
 
 /// Null device.
 ///
@@ -59,32 +61,52 @@ impl<Idx, V, const N: usize> TryAddress<Idx, V> for Null<V, N>
 where
     Idx: Value,
     V: Value,
-    usize: From<Idx>,
 {
     type Error = Error<Idx>;
 
     fn try_read(&self, index: Idx) -> Result<V, Self::Error> {
-        match N {
-            len @ 0 | len if len > usize::from(index) => Ok(self.0),
-            _ => Err(Error::Bounds(index)),
-        }
+        (N == 0 || index.to_usize().is_some_and(|index| index < N))
+            .then_some(self.0)
+            .ok_or(Error::Bounds(index))
     }
 
     fn try_write(&mut self, index: Idx, _: V) -> Result<(), Self::Error> {
-        match N {
-            len @ 0 | len if len > usize::from(index) => Ok(()),
-            _ => Err(Error::Bounds(index)),
-        }
+        (N == 0 || index.to_usize().is_some_and(|index| index < N))
+            .then_some(())
+            .ok_or(Error::Bounds(index))
     }
 }
 
-impl<V, const N: usize> Block for Null<V, N> where V: Value {}
+impl<V, const N: usize> Block for Null<V, N>
+where
+    V: Value,
+{
+    #[cfg(feature = "state")]
+    fn save_state(&self, state: &mut state::Writer) {
+        state.section("null", 1, |state| state.value(self.0));
+    }
+
+    #[cfg(feature = "state")]
+    fn load_state(&mut self, state: &mut state::Reader) -> Result<(), state::Error> {
+        state.section("null", 1, |state, _| {
+            self.0 = state.value()?;
+            Ok(())
+        })
+    }
+}
 
 impl<Idx, V, const N: usize> Device<Idx, V> for Null<V, N>
 where
     Idx: Value,
     V: Value,
 {
+    fn try_read_dyn(&self, index: Idx) -> Result<V, dev::Error> {
+        self.try_read(index).map_err(Into::into)
+    }
+
+    fn try_write_dyn(&mut self, index: Idx, value: V) -> Result<(), dev::Error> {
+        self.try_write(index, value).map_err(Into::into)
+    }
 }
 
 /// A type specifying general categories of [`Null`] error.
@@ -131,4 +153,14 @@ mod tests {
             .map(|index| null.read(index))
             .all(|byte| byte == 0));
     }
+
+    #[test]
+    fn try_address_bounds_works() {
+        let null: Null<u8, 0x100> = Null::with(0xaa);
+        assert_eq!(null.try_read(0x0ffusize).ok(), Some(0xaa));
+        assert!(null.try_read(0x100usize).is_err());
+        // Unbounded nulls accept any index
+        let null: Null<u8> = Null::with(0xaa);
+        assert_eq!(null.try_read(usize::MAX).ok(), Some(0xaa));
+    }
 }
@@ -4,9 +4,9 @@ use rand::distributions::Standard;
 use rand::prelude::Distribution;
 use thiserror::Error;
 
-use super::Device;
 use crate::arch::{Address, TryAddress, Value};
 use crate::blk::Block;
+use crate::dev::{self, Device};
 
 /// Random device.
 ///
@@ -50,23 +50,20 @@ impl<Idx, V, const N: usize> TryAddress<Idx, V> for Random<V, N>
 where
     Idx: Value,
     V: Value,
-    usize: From<Idx>,
     Standard: Distribution<V>,
 {
     type Error = Error<Idx>;
 
     fn try_read(&self, index: Idx) -> Result<V, Self::Error> {
-        match N {
-            len @ 0 | len if len > usize::from(index) => Ok(rand::random()),
-            _ => Err(Error::Bounds(index)),
-        }
+        (N == 0 || index.to_usize().is_some_and(|index| index < N))
+            .then(rand::random)
+            .ok_or(Error::Bounds(index))
     }
 
     fn try_write(&mut self, index: Idx, _: V) -> Result<(), Self::Error> {
-        match N {
-            len @ 0 | len if len > usize::from(index) => Ok(()),
-            _ => Err(Error::Bounds(index)),
-        }
+        (N == 0 || index.to_usize().is_some_and(|index| index < N))
+            .then_some(())
+            .ok_or(Error::Bounds(index))
     }
 }
 
@@ -83,6 +80,13 @@ where
     V: Value,
     Standard: Distribution<V>,
 {
+    fn try_read_dyn(&self, index: Idx) -> Result<V, dev::Error> {
+        self.try_read(index).map_err(Into::into)
+    }
+
+    fn try_write_dyn(&mut self, index: Idx, value: V) -> Result<(), dev::Error> {
+        self.try_write(index, value).map_err(Into::into)
+    }
 }
 
 /// A type specifying general categories of [`Random`] error.
@@ -13,18 +13,24 @@
 
 mod arch;
 mod blk;
-mod clk;
 mod fsm;
 mod pcb;
 mod share;
 
 pub mod bus;
+pub mod clk;
 pub mod dev;
+pub mod load;
 pub mod mem;
 pub mod reg;
+#[cfg(feature = "state")]
+pub mod state;
+pub mod sym;
 pub mod wired;
 
-pub use self::arch::{Address, Cell, Location, TryAddress};
+pub use self::arch::{
+    Access, Address, AddressExt, Cell, Context, Location, TryAddress, TryAddressExt,
+};
 pub use self::blk::{Block, Linked};
 pub use self::clk::Clock;
 pub use self::fsm::Machine;
@@ -0,0 +1,363 @@
+//! ELF parser.
+//!
+//! See: <https://en.wikipedia.org/wiki/Executable_and_Linkable_Format>
+
+use super::{address, Error, Image, Segment, Symbol};
+
+/// Loadable program segment type.
+const PT_LOAD: u64 = 1;
+/// Symbol table section type.
+const SHT_SYMTAB: u64 = 2;
+/// Dynamic symbol table section type.
+const SHT_DYNSYM: u64 = 11;
+/// Undefined section index.
+const SHN_UNDEF: u64 = 0;
+/// Section symbol type.
+const STT_SECTION: u8 = 3;
+/// File symbol type.
+const STT_FILE: u8 = 4;
+
+/// Maximum zero-filled length of a segment.
+///
+/// As a segment's memory size is untrusted, this prevents a malformed
+/// executable from requesting an arbitrarily large allocation.
+const MAX_FILL: u64 = 1 << 24;
+
+/// ELF file reader.
+///
+/// Reads fields according to the file's class and byte order.
+struct Elf<'a> {
+    data: &'a [u8],
+    wide: bool,
+    big: bool,
+}
+
+impl<'a> Elf<'a> {
+    /// Selects a field offset (or size) by the file's class.
+    fn sel(&self, n32: u64, n64: u64) -> u64 {
+        if self.wide {
+            n64
+        } else {
+            n32
+        }
+    }
+
+    /// Reads `len` bytes at an offset.
+    fn bytes(&self, off: u64, len: u64) -> Result<&'a [u8], Error> {
+        let start = usize::try_from(off).ok();
+        let end = off
+            .checked_add(len)
+            .and_then(|end| usize::try_from(end).ok());
+        start
+            .zip(end)
+            .and_then(|(start, end)| self.data.get(start..end))
+            .ok_or(Error::Elf("unexpected end of file"))
+    }
+
+    /// Reads a byte at an offset.
+    fn byte(&self, off: u64) -> Result<u8, Error> {
+        self.bytes(off, 1).map(|bytes| bytes[0])
+    }
+
+    /// Reads an unsigned integer of `len` bytes at an offset.
+    fn uint(&self, off: u64, len: u64) -> Result<u64, Error> {
+        let bytes = self.bytes(off, len)?;
+        if self.big {
+            Ok(address(bytes))
+        } else {
+            Ok(bytes
+                .iter()
+                .rev()
+                .fold(0, |int, &byte| (int << 8) | u64::from(byte)))
+        }
+    }
+
+    /// Reads a half-word at an offset.
+    fn half(&self, off: u64) -> Result<u64, Error> {
+        self.uint(off, 2)
+    }
+
+    /// Reads a word at an offset.
+    fn word(&self, off: u64) -> Result<u64, Error> {
+        self.uint(off, 4)
+    }
+
+    /// Reads a class-sized address (or offset) at an offset.
+    fn addr(&self, off: u64) -> Result<u64, Error> {
+        self.uint(off, self.sel(4, 8))
+    }
+
+    /// Returns the offsets of a table's entries.
+    ///
+    /// Offsets are clamped to the end of the file, such that reading from an
+    /// out-of-bounds entry fails rather than overflows.
+    fn table(&self, off: u64, size: u64, num: u64) -> impl Iterator<Item = u64> {
+        let end = self.data.len() as u64;
+        (0..num).map(move |idx| off.saturating_add(idx * size).min(end))
+    }
+}
+
+/// Parses an [`Image`] from an ELF executable.
+pub(super) fn parse(data: &[u8]) -> Result<Image, Error> {
+    // Check the identification
+    if data.get(..4) != Some(b"\x7fELF") {
+        return Err(Error::Elf("invalid magic number"));
+    }
+    let wide = match data.get(4) {
+        Some(1) => false,
+        Some(2) => true,
+        _ => return Err(Error::Elf("invalid class")),
+    };
+    let big = match data.get(5) {
+        Some(1) => false,
+        Some(2) => true,
+        _ => return Err(Error::Elf("invalid byte order")),
+    };
+    let elf = Elf { data, wide, big };
+    let mut image = Image::new();
+    image.set_entry(Some(elf.addr(24)?));
+    segments(&elf, &mut image)?;
+    symbols(&elf, &mut image)?;
+    Ok(image)
+}
+
+/// Loads each `PT_LOAD` program segment into an image.
+fn segments(elf: &Elf, image: &mut Image) -> Result<(), Error> {
+    let phdrs = elf.table(
+        elf.addr(elf.sel(28, 32))?,
+        elf.half(elf.sel(42, 54))?,
+        elf.half(elf.sel(44, 56))?,
+    );
+    for ph in phdrs {
+        if elf.word(ph)? != PT_LOAD {
+            continue;
+        }
+        let offset = elf.addr(ph + elf.sel(4, 8))?;
+        let paddr = elf.addr(ph + elf.sel(12, 24))?;
+        let filesz = elf.addr(ph + elf.sel(16, 32))?;
+        let memsz = elf.addr(ph + elf.sel(20, 40))?;
+        if filesz > memsz {
+            return Err(Error::Elf("segment file size exceeds memory size"));
+        }
+        if memsz == 0 {
+            continue;
+        }
+        if memsz - filesz > MAX_FILL {
+            return Err(Error::Elf("segment too large"));
+        }
+        let mut data = elf.bytes(offset, filesz)?.to_vec();
+        // Zero-fill the remainder of the segment
+        data.resize(
+            usize::try_from(memsz).map_err(|_| Error::Elf("segment too large"))?,
+            0,
+        );
+        image.push(Segment {
+            addr: paddr,
+            data,
+            line: None,
+        });
+    }
+    Ok(())
+}
+
+/// Collects symbols from the symbol table into an image.
+fn symbols(elf: &Elf, image: &mut Image) -> Result<(), Error> {
+    let shdrs: Vec<u64> = elf
+        .table(
+            elf.addr(elf.sel(32, 40))?,
+            elf.half(elf.sel(46, 58))?,
+            elf.half(elf.sel(48, 60))?,
+        )
+        .collect();
+    let mut tabs = Vec::new();
+    for &sh in &shdrs {
+        tabs.push((elf.word(sh + 4)?, sh));
+    }
+    // Prefer the full symbol table, falling back to dynamic symbols
+    let kind = if tabs.iter().any(|&(kind, _)| kind == SHT_SYMTAB) {
+        SHT_SYMTAB
+    } else {
+        SHT_DYNSYM
+    };
+    for sh in tabs
+        .into_iter()
+        .filter_map(|(it, sh)| (it == kind).then_some(sh))
+    {
+        let offset = elf.addr(sh + elf.sel(16, 24))?;
+        let size = elf.addr(sh + elf.sel(20, 32))?;
+        let link = elf.word(sh + elf.sel(24, 40))?;
+        let entsize = elf.addr(sh + elf.sel(36, 56))?;
+        if entsize == 0 {
+            return Err(Error::Elf("invalid symbol entry size"));
+        }
+        // Locate the associated string table
+        let strsh = usize::try_from(link)
+            .ok()
+            .and_then(|link| shdrs.get(link))
+            .ok_or(Error::Elf("invalid string table index"))?;
+        let strtab = elf.bytes(
+            elf.addr(strsh + elf.sel(16, 24))?,
+            elf.addr(strsh + elf.sel(20, 32))?,
+        )?;
+        for sym in elf.table(offset, entsize, size / entsize) {
+            let name = elf.word(sym)?;
+            let info = elf.byte(sym + elf.sel(12, 4))?;
+            let shndx = elf.half(sym + elf.sel(14, 6))?;
+            let value = elf.addr(sym + elf.sel(4, 8))?;
+            let size = elf.addr(sym + elf.sel(8, 16))?;
+            if name == 0 || shndx == SHN_UNDEF || matches!(info & 0xf, STT_SECTION | STT_FILE) {
+                continue;
+            }
+            let name = usize::try_from(name)
+                .ok()
+                .and_then(|name| strtab.get(name..))
+                .and_then(|name| name.split(|&byte| byte == 0).next())
+                .ok_or(Error::Elf("invalid symbol name"))?;
+            image.syms.push(Symbol {
+                name: String::from_utf8_lossy(name).into_owned(),
+                addr: value,
+                size,
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::{Bus, Mux};
+    use crate::dev::Device;
+    use crate::load::Mode;
+    use crate::mem::Ram;
+    use crate::Address;
+
+    /// Assembles a minimal executable.
+    fn build(wide: bool, big: bool) -> Vec<u8> {
+        let mut buf = vec![0u8; 0x300];
+        let sel = |n32: usize, n64: usize| if wide { n64 } else { n32 };
+        let mut put = |off: usize, len: usize, val: u64| {
+            let bytes = if big {
+                val.to_be_bytes()[8 - len..].to_vec()
+            } else {
+                val.to_le_bytes()[..len].to_vec()
+            };
+            buf[off..off + len].copy_from_slice(&bytes);
+        };
+        let word = sel(4, 8);
+        // Header
+        put(24, word, 0x1000);
+        put(sel(28, 32), word, 0x40);
+        put(sel(32, 40), word, 0x200);
+        put(sel(42, 54), 2, sel(32, 56) as u64);
+        put(sel(44, 56), 2, 2);
+        put(sel(46, 58), 2, sel(40, 64) as u64);
+        put(sel(48, 60), 2, 3);
+        // Program headers
+        let ph = 0x40;
+        put(ph, 4, PT_LOAD);
+        put(ph + sel(4, 8), word, 0x100);
+        put(ph + sel(8, 16), word, 0x8000_1000);
+        put(ph + sel(12, 24), word, 0x1000);
+        put(ph + sel(16, 32), word, 4);
+        put(ph + sel(20, 40), word, 8);
+        let ph = ph + sel(32, 56);
+        put(ph, 4, 4);
+        // Symbol table
+        let ent = sel(16, 24);
+        let syms = [
+            (1, 0x1000, 4, 0x12),
+            (8, 0x1004, 4, 0x11),
+            (0, 0x1000, 0, 0x03),
+        ];
+        for (idx, (name, value, size, info)) in syms.into_iter().enumerate() {
+            let sym = 0x140 + (idx + 1) * ent;
+            put(sym, 4, name);
+            put(sym + sel(4, 8), word, value);
+            put(sym + sel(8, 16), word, size);
+            put(sym + sel(12, 4), 1, info);
+            put(sym + sel(14, 6), 2, 1);
+        }
+        // Section headers
+        let names = b"\0_start\0buf\0";
+        let sh = 0x200 + sel(40, 64);
+        put(sh + 4, 4, SHT_SYMTAB);
+        put(sh + sel(16, 24), word, 0x140);
+        put(sh + sel(20, 32), word, (4 * ent) as u64);
+        put(sh + sel(24, 40), 4, 2);
+        put(sh + sel(36, 56), word, ent as u64);
+        let sh = sh + sel(40, 64);
+        put(sh + 4, 4, 3);
+        put(sh + sel(16, 24), word, 0x120);
+        put(sh + sel(20, 32), word, names.len() as u64);
+        // Identification
+        buf[..4].copy_from_slice(b"\x7fELF");
+        buf[4] = if wide { 2 } else { 1 };
+        buf[5] = if big { 2 } else { 1 };
+        buf[6] = 1;
+        // Contents
+        buf[0x100..0x104].copy_from_slice(&[0x01, 0x02, 0x03, 0x04]);
+        buf[0x120..0x120 + names.len()].copy_from_slice(names);
+        buf
+    }
+
+    #[test]
+    fn parse_works() {
+        for (wide, big) in [(false, false), (false, true), (true, false), (true, true)] {
+            let image = parse(&build(wide, big)).unwrap();
+            assert_eq!(image.entry(), Some(0x1000));
+            assert_eq!(
+                image.segments(),
+                [Segment {
+                    addr: 0x1000,
+                    data: vec![0x01, 0x02, 0x03, 0x04, 0x00, 0x00, 0x00, 0x00],
+                    line: None,
+                }]
+            );
+            assert_eq!(
+                image.symbols(),
+                [
+                    Symbol {
+                        name: "_start".to_string(),
+                        addr: 0x1000,
+                        size: 4,
+                    },
+                    Symbol {
+                        name: "buf".to_string(),
+                        addr: 0x1004,
+                        size: 4,
+                    },
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn load_works() {
+        let mut bus = Bus::<u16, u8>::new();
+        bus.map(0x1000..=0x10ff, Ram::from(&[0xff; 0x100]).to_dynamic());
+        let image = parse(&build(false, true)).unwrap();
+        image.load(&mut bus, Mode::Write).unwrap();
+        assert_eq!(bus.read(0x1000), 0x01);
+        assert_eq!(bus.read(0x1003), 0x04);
+        // BSS is zero-filled
+        assert!((0x1004..0x1008).all(|index| bus.read(index) == 0x00));
+        assert_eq!(bus.read(0x1008), 0xff);
+    }
+
+    #[test]
+    fn parse_fails() {
+        assert!(matches!(parse(b"\x7fELG"), Err(Error::Elf(_))));
+        assert!(matches!(parse(b"\x7fELF\x03\x01"), Err(Error::Elf(_))));
+        let mut elf = build(true, false);
+        elf.truncate(0x80);
+        assert!(matches!(
+            parse(&elf),
+            Err(Error::Elf("unexpected end of file"))
+        ));
+        // Memory size is bounded
+        let mut elf = build(true, false);
+        elf[0x40 + 40..0x40 + 48].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(parse(&elf), Err(Error::Elf("segment too large"))));
+    }
+}
@@ -0,0 +1,166 @@
+//! Intel HEX parser.
+//!
+//! See: <https://en.wikipedia.org/wiki/Intel_HEX>
+
+use super::{address, decode, Error, Image, Segment};
+
+/// Parses an [`Image`] from Intel HEX.
+pub(super) fn parse(src: &str) -> Result<Image, Error> {
+    let mut image = Image::new();
+    let mut base = 0;
+    for (line, text) in (1..).zip(src.lines()) {
+        let text = text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let Some(digits) = text.strip_prefix(':') else {
+            return Err(Error::Format {
+                line,
+                what: "missing start code",
+            });
+        };
+        let bytes = decode(line, digits)?;
+        // Check the record's framing
+        let Some(&len) = bytes.first() else {
+            return Err(Error::Format {
+                line,
+                what: "record too short",
+            });
+        };
+        if bytes.len() != usize::from(len) + 5 {
+            return Err(Error::Format {
+                line,
+                what: "byte count does not match record length",
+            });
+        }
+        let (body, found) = (&bytes[..bytes.len() - 1], bytes[bytes.len() - 1]);
+        let expected = body
+            .iter()
+            .fold(0u8, |sum, &byte| sum.wrapping_add(byte))
+            .wrapping_neg();
+        if expected != found {
+            return Err(Error::Checksum {
+                line,
+                expected,
+                found,
+            });
+        }
+        // Interpret the record
+        let offset = address(&body[1..3]);
+        let data = &body[4..];
+        let width = |n| {
+            if data.len() == n {
+                Ok(address(data))
+            } else {
+                Err(Error::Format {
+                    line,
+                    what: "invalid data length for record type",
+                })
+            }
+        };
+        match body[3] {
+            // Data
+            0x00 if data.is_empty() => (),
+            0x00 => image.push(Segment {
+                addr: base + offset,
+                data: data.to_vec(),
+                line: Some(line),
+            }),
+            // End of file
+            0x01 => return Ok(image),
+            // Extended segment address
+            0x02 => base = width(2)? << 4,
+            // Start segment address
+            0x03 => {
+                let addr = width(4)?;
+                image.set_entry(Some(((addr >> 16) << 4) + (addr & 0xffff)));
+            }
+            // Extended linear address
+            0x04 => base = width(2)? << 16,
+            // Start linear address
+            0x05 => image.set_entry(Some(width(4)?)),
+            kind => return Err(Error::Kind { line, kind }),
+        }
+    }
+    Err(Error::Eof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_works() {
+        let image = parse(
+            ":0400100001020304E2\n\
+             :020000040001F9\n\
+             :02000000AABB99\n\
+             :00002000E0\n\
+             :0400000500000123D3\n\
+             :00000001FF\n",
+        )
+        .unwrap();
+        assert_eq!(
+            image.segments(),
+            [
+                Segment {
+                    addr: 0x0010,
+                    data: vec![0x01, 0x02, 0x03, 0x04],
+                    line: Some(1),
+                },
+                Segment {
+                    addr: 0x10000,
+                    data: vec![0xaa, 0xbb],
+                    line: Some(3),
+                },
+            ]
+        );
+        assert_eq!(image.entry(), Some(0x0123));
+    }
+
+    #[test]
+    fn parse_segment_address_works() {
+        let image = parse(
+            ":020000021000EC\n\
+             :01002000558A\n\
+             :0400000300100020C9\n\
+             :00000001FF\n",
+        )
+        .unwrap();
+        assert_eq!(image.segments()[0].addr, 0x10020);
+        assert_eq!(image.entry(), Some(0x0120));
+    }
+
+    #[test]
+    fn parse_checksum_fails() {
+        assert!(matches!(
+            parse("\n:0400100001020304E3\n"),
+            Err(Error::Checksum {
+                line: 2,
+                expected: 0xe2,
+                found: 0xe3,
+            })
+        ));
+    }
+
+    #[test]
+    fn parse_format_fails() {
+        assert!(matches!(
+            parse("0400100001020304E2"),
+            Err(Error::Format { line: 1, .. })
+        ));
+        assert!(matches!(
+            parse(":0500100001020304E1"),
+            Err(Error::Format { line: 1, .. })
+        ));
+        assert!(matches!(
+            parse(":04001000010203G4E2"),
+            Err(Error::Format { line: 1, .. })
+        ));
+        assert!(matches!(
+            parse(":00000006FA"),
+            Err(Error::Kind { line: 1, kind: 6 })
+        ));
+        assert!(matches!(parse(":0400100001020304E2"), Err(Error::Eof)));
+    }
+}
@@ -0,0 +1,547 @@
+//! Firmware image loaders.
+//!
+//! # Usage
+//!
+//! An [`Image`] is parsed from firmware produced by an external toolchain,
+//! then loaded onto any [`Mux`], such as a [`Bus`](crate::bus::Bus):
+//!
+//! ```
+//! use remus::bus::{Bus, Mux};
+//! use remus::dev::Device;
+//! use remus::load::{Image, Mode};
+//! use remus::mem::Ram;
+//! use remus::Address;
+//!
+//! let mut bus = Bus::<u16, u8>::new();
+//! bus.map(0x0000..=0x00ff, Ram::<u8, 0x100>::new().to_dynamic());
+//!
+//! let image = Image::ihex(":0400100001020304E2\n:00000001FF\n").unwrap();
+//! image.load(&mut bus, Mode::Write).unwrap();
+//! assert_eq!(bus.read(0x0013), 0x04);
+//! ```
+//!
+//! The following formats are supported:
+//!
+//! | Format             | Constructor      |
+//! |--------------------|------------------|
+//! | Intel HEX          | [`Image::ihex`]  |
+//! | Motorola S-record  | [`Image::srec`]  |
+//! | Raw binary         | [`Image::raw`]   |
+//! | ELF32/ELF64        | [`Image::elf`]   |
+//!
+//! Before any data is written, the entire image is validated against the
+//! target's [effective](Mux::effective) memory map, such that an image which
+//! does not fit the map leaves the target unchanged. However, devices may still
+//! reject individual writes (e.g. a [`Rom`](crate::mem::Rom) mapped as
+//! writable), which is only discovered once loading is underway. In that case,
+//! any preceding bytes of the image will have already been written.
+
+use std::fmt::Display;
+
+use thiserror::Error;
+
+use crate::arch::{Access, Context, Value};
+use crate::bus::{self, Mux, Perm, Region};
+use crate::dev;
+
+mod elf;
+mod ihex;
+mod srec;
+
+/// Firmware image.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Image {
+    segs: Vec<Segment>,
+    syms: Vec<Symbol>,
+    entry: Option<u64>,
+}
+
+impl Image {
+    /// Constructs a new, empty `Image`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses an `Image` from Intel HEX.
+    ///
+    /// # Errors
+    ///
+    /// Errors if any record is malformed, or the end-of-file record is
+    /// missing.
+    pub fn ihex(src: &str) -> Result<Self, Error> {
+        ihex::parse(src)
+    }
+
+    /// Parses an `Image` from Motorola S-records.
+    ///
+    /// # Errors
+    ///
+    /// Errors if any record is malformed.
+    pub fn srec(src: &str) -> Result<Self, Error> {
+        srec::parse(src)
+    }
+
+    /// Parses an `Image` from an ELF32 or ELF64 executable.
+    ///
+    /// Each `PT_LOAD` segment is placed at its physical address, with any
+    /// memory beyond its file contents (e.g. `.bss`) zero-filled. Symbols are
+    /// collected from the symbol table, if present.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the executable is malformed, or if any segment would be
+    /// zero-filled beyond 16 MiB.
+    pub fn elf(data: &[u8]) -> Result<Self, Error> {
+        elf::parse(data)
+    }
+
+    /// Constructs an `Image` from a raw binary, placed at `base`.
+    ///
+    /// An empty binary produces an image without any segments.
+    #[must_use]
+    pub fn raw(base: u64, data: &[u8]) -> Self {
+        let mut this = Self::new();
+        if !data.is_empty() {
+            this.push(Segment {
+                addr: base,
+                data: data.to_vec(),
+                line: None,
+            });
+        }
+        this
+    }
+
+    /// Returns the image's segments, in order of appearance.
+    #[must_use]
+    pub fn segments(&self) -> &[Segment] {
+        &self.segs
+    }
+
+    /// Returns the image's symbols, in order of appearance.
+    #[must_use]
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.syms
+    }
+
+    /// Returns the image's entry point, if specified.
+    #[must_use]
+    pub fn entry(&self) -> Option<u64> {
+        self.entry
+    }
+
+    /// Appends a segment to the image.
+    pub fn push(&mut self, seg: Segment) {
+        self.segs.push(seg);
+    }
+
+    /// Sets the image's entry point.
+    pub fn set_entry(&mut self, entry: Option<u64>) {
+        self.entry = entry;
+    }
+
+    /// Loads the image onto a multiplexer.
+    ///
+    /// # Errors
+    ///
+    /// Errors if any byte of the image does not land on a mapped region
+    /// permitting the access `mode`, or if a device rejects a write. Upon a
+    /// mapping error, the target is left unchanged. Upon a device error, the
+    /// bytes preceding the faulting address remain written.
+    pub fn load<Idx, M>(&self, mux: &mut M, mode: Mode) -> Result<(), Error>
+    where
+        Idx: Value,
+        M: Mux<Idx, u8> + ?Sized,
+    {
+        // Validate against the memory map before writing anything
+        let map = mux.effective();
+        for seg in &self.segs {
+            let mut hit: Option<&Region<Idx, u8>> = None;
+            for (addr, index) in seg.indices()? {
+                if !hit.is_some_and(|it| it.range.contains(&index)) {
+                    hit = map.iter().find(|it| it.range.contains(&index));
+                }
+                let Some(it) = hit else {
+                    return Err(Error::Unmapped {
+                        line: seg.line,
+                        addr,
+                    });
+                };
+                if mode == Mode::Write && !it.perm.contains(Perm::W) {
+                    return Err(Error::Protected {
+                        line: seg.line,
+                        addr,
+                    });
+                }
+            }
+        }
+        // Write each segment
+        for seg in &self.segs {
+            for ((addr, index), &byte) in seg.indices()?.zip(&seg.data) {
+                match mode {
+                    Mode::Write => {
+                        mux.try_write(index, byte)
+                            .map_err(|err| Error::from_bus(err, seg.line, addr))?;
+                    }
+                    Mode::Poke => {
+                        let ctx = Context::new(Access::Debug, 0);
+                        mux.try_write_ctx(index, byte, ctx)
+                            .map_err(|source| Error::Device {
+                                line: seg.line,
+                                addr,
+                                source,
+                            })?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Image segment.
+///
+/// A contiguous run of bytes to be loaded at an address.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Segment {
+    /// Load address.
+    pub addr: u64,
+    /// Segment contents.
+    pub data: Vec<u8>,
+    /// Source line of the record defining the segment, if any.
+    pub line: Option<usize>,
+}
+
+impl Segment {
+    /// Returns an iterator over the segment's addresses and their indices.
+    fn indices<Idx: Value>(&self) -> Result<impl Iterator<Item = (u64, Idx)> + '_, Error> {
+        let range = |addr| Error::Range {
+            line: self.line,
+            addr,
+        };
+        // Ensure the segment is non-empty and entirely addressable
+        let last = (self.data.len() as u64)
+            .checked_sub(1)
+            .ok_or(Error::Empty {
+                line: self.line,
+                addr: self.addr,
+            })?;
+        let end = self.addr.checked_add(last).ok_or(range(self.addr))?;
+        num::cast::<u64, Idx>(end).ok_or(range(end))?;
+        Ok((self.addr..=end).map(|addr| (addr, num::cast(addr).unwrap())))
+    }
+}
+
+/// Image symbol.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Symbol {
+    /// Symbol name.
+    pub name: String,
+    /// Symbol address.
+    pub addr: u64,
+    /// Symbol size, in bytes.
+    pub size: u64,
+}
+
+/// Image load access mode.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Mode {
+    /// Load using [`TryAddress::try_write`](crate::TryAddress::try_write), respecting mapping permissions.
+    #[default]
+    Write,
+    /// Load using [debug](Access::Debug) writes, as with
+    /// [`Device::poke`](crate::dev::Device::poke), bypassing mapping permissions.
+    ///
+    /// Useful for loading firmware into read-only memories.
+    Poke,
+}
+
+/// Formats an optional source line for an error message.
+fn at(line: Option<usize>) -> impl Display {
+    line.map(|line| format!(" (line {line})"))
+        .unwrap_or_default()
+}
+
+/// A type specifying general categories of [`Image`] error.
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("malformed record on line {line}: {what}")]
+    Format {
+        /// Source line of the record.
+        line: usize,
+        /// Description of the problem.
+        what: &'static str,
+    },
+    #[error("checksum mismatch on line {line}: expected {expected:#04x}, found {found:#04x}")]
+    Checksum {
+        /// Source line of the record.
+        line: usize,
+        /// Checksum computed from the record.
+        expected: u8,
+        /// Checksum stored in the record.
+        found: u8,
+    },
+    #[error("unsupported record type on line {line}: {kind}")]
+    Kind {
+        /// Source line of the record.
+        line: usize,
+        /// Record type.
+        kind: u8,
+    },
+    #[error("missing end-of-file record")]
+    Eof,
+    #[error("malformed ELF: {0}")]
+    Elf(&'static str),
+    #[error("address {addr:#x} is out of range{}", at(*.line))]
+    Range {
+        /// Source line of the record, if any.
+        line: Option<usize>,
+        /// Faulting address.
+        addr: u64,
+    },
+    #[error("empty segment at address {addr:#x}{}", at(*.line))]
+    Empty {
+        /// Source line of the record, if any.
+        line: Option<usize>,
+        /// Segment address.
+        addr: u64,
+    },
+    #[error("address {addr:#x} is not mapped{}", at(*.line))]
+    Unmapped {
+        /// Source line of the record, if any.
+        line: Option<usize>,
+        /// Faulting address.
+        addr: u64,
+    },
+    #[error("address {addr:#x} is not writable{}", at(*.line))]
+    Protected {
+        /// Source line of the record, if any.
+        line: Option<usize>,
+        /// Faulting address.
+        addr: u64,
+    },
+    #[error("device error at address {addr:#x}{}", at(*.line))]
+    Device {
+        /// Source line of the record, if any.
+        line: Option<usize>,
+        /// Faulting address.
+        addr: u64,
+        /// Underlying device error.
+        source: dev::Error,
+    },
+}
+
+impl Error {
+    /// Attributes a bus error to a source line.
+    fn from_bus<Idx: Value>(err: bus::Error<Idx>, line: Option<usize>, addr: u64) -> Self {
+        match err {
+            bus::Error::Unmapped(_) => Self::Unmapped { line, addr },
+            bus::Error::Protected { .. } => Self::Protected { line, addr },
+            bus::Error::Device { source, .. } => Self::Device { line, addr, source },
+        }
+    }
+}
+
+/// Decodes a record's hexadecimal digits into bytes.
+fn decode(line: usize, digits: &str) -> Result<Vec<u8>, Error> {
+    if digits.len() % 2 != 0 {
+        return Err(Error::Format {
+            line,
+            what: "odd number of digits",
+        });
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| {
+            digits
+                .get(i..i + 2)
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or(Error::Format {
+                    line,
+                    what: "invalid hexadecimal digit",
+                })
+        })
+        .collect()
+}
+
+/// Reads a big-endian address from bytes.
+fn address(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(0, |addr, &byte| (addr << 8) | u64::from(byte))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::Bus;
+    use crate::dev::Device;
+    use crate::mem::{Ram, Rom};
+    use crate::Address;
+
+    fn setup() -> Bus<u16, u8> {
+        let mut bus = Bus::new();
+        bus.map(0x0000..=0x00ff, Rom::<u8, 0x100>::new().to_dynamic());
+        let ram = bus.map(0x0100..=0x01ff, Ram::<u8, 0x100>::new().to_dynamic());
+        bus.protect(ram, Perm::RW);
+        bus
+    }
+
+    #[test]
+    fn load_write_works() {
+        let mut bus = setup();
+        let image = Image::raw(0x0180, &[0xaa, 0xbb]);
+        image.load(&mut bus, Mode::Write).unwrap();
+        assert_eq!(bus.read(0x0180), 0xaa);
+        assert_eq!(bus.read(0x0181), 0xbb);
+    }
+
+    #[test]
+    fn load_poke_works() {
+        let mut bus = setup();
+        let image = Image::raw(0x00fe, &[0xaa, 0xbb, 0xcc]);
+        image.load(&mut bus, Mode::Poke).unwrap();
+        assert_eq!(bus.read(0x00ff), 0xbb);
+        assert_eq!(bus.read(0x0100), 0xcc);
+    }
+
+    #[test]
+    fn load_unmapped_fails() {
+        let mut bus = setup();
+        let image = Image::ihex(":0201FF00AABB99\n:00000001FF\n").unwrap();
+        let err = image.load(&mut bus, Mode::Write).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Unmapped {
+                line: Some(1),
+                addr: 0x0200
+            }
+        ));
+        assert_eq!(err.to_string(), "address 0x200 is not mapped (line 1)");
+        // Nothing is written upon error
+        assert_eq!(bus.read(0x01ff), 0x00);
+    }
+
+    #[test]
+    fn load_protected_fails() {
+        let mut bus = setup();
+        let rom = bus.regions()[0].handle;
+        bus.protect(rom, Perm::RX);
+        let mut image = Image::raw(0x0100, &[0xaa]);
+        image.push(Segment {
+            addr: 0x0000,
+            data: vec![0xbb],
+            line: Some(2),
+        });
+        assert!(matches!(
+            image.load(&mut bus, Mode::Write),
+            Err(Error::Protected {
+                line: Some(2),
+                addr: 0x0000
+            })
+        ));
+        // Nothing is written upon error
+        assert_eq!(bus.read(0x0100), 0x00);
+        // Poking bypasses permissions
+        image.load(&mut bus, Mode::Poke).unwrap();
+        assert_eq!(bus.read(0x0000), 0xbb);
+    }
+
+    #[test]
+    fn load_device_fails() {
+        let mut bus = setup();
+        let mut image = Image::raw(0x0100, &[0xaa]);
+        image.push(Segment {
+            addr: 0x0011,
+            data: vec![0xbb],
+            line: None,
+        });
+        assert!(matches!(
+            image.load(&mut bus, Mode::Write),
+            Err(Error::Device {
+                line: None,
+                addr: 0x0011,
+                ..
+            })
+        ));
+        // Preceding bytes remain written
+        assert_eq!(bus.read(0x0100), 0xaa);
+    }
+
+    #[test]
+    fn load_poke_device_fails() {
+        let mut bus = Bus::<u16, u8>::new();
+        bus.map(0x0000..=0x00ff, Rom::<u8, 0x10>::new().to_dynamic());
+        let image = Image::ihex(":02000E00AABB8B\n:01001000CC23\n:00000001FF\n").unwrap();
+        // Pokes beyond the end of the device are reported by line
+        assert!(matches!(
+            image.load(&mut bus, Mode::Poke),
+            Err(Error::Device {
+                line: Some(2),
+                addr: 0x0010,
+                ..
+            })
+        ));
+        assert_eq!(bus.read(0x000f), 0xbb);
+    }
+
+    #[test]
+    fn load_range_fails() {
+        let mut bus = setup();
+        let image = Image::raw(0xffff, &[0xaa, 0xbb]);
+        assert!(matches!(
+            image.load(&mut bus, Mode::Poke),
+            Err(Error::Range {
+                line: None,
+                addr: 0x10000
+            })
+        ));
+    }
+
+    #[test]
+    fn load_empty_fails() {
+        let mut bus = setup();
+        let mut image = Image::raw(0x0100, &[]);
+        assert!(image.segments().is_empty());
+        image.push(Segment {
+            addr: 0x0100,
+            data: Vec::new(),
+            line: Some(3),
+        });
+        assert!(matches!(
+            image.load(&mut bus, Mode::Write),
+            Err(Error::Empty {
+                line: Some(3),
+                addr: 0x0100
+            })
+        ));
+    }
+
+    #[test]
+    fn segment_indices_works() {
+        // Segments may end at the very top of the address space
+        let seg = Segment {
+            addr: u64::MAX - 1,
+            data: vec![0xaa, 0xbb],
+            line: None,
+        };
+        let addrs: Vec<_> = seg
+            .indices::<u64>()
+            .unwrap()
+            .map(|(addr, _)| addr)
+            .collect();
+        assert_eq!(addrs, [u64::MAX - 1, u64::MAX]);
+        // ... but not beyond it
+        let seg = Segment {
+            addr: u64::MAX,
+            ..seg
+        };
+        assert!(matches!(
+            seg.indices::<u64>(),
+            Err(Error::Range {
+                line: None,
+                addr: u64::MAX
+            })
+        ));
+    }
+}
@@ -0,0 +1,162 @@
+//! Motorola S-record parser.
+//!
+//! See: <https://en.wikipedia.org/wiki/SREC_(file_format)>
+
+use super::{address, decode, Error, Image, Segment};
+
+/// Parses an [`Image`] from Motorola S-records.
+pub(super) fn parse(src: &str) -> Result<Image, Error> {
+    let mut image = Image::new();
+    let mut count = 0;
+    for (line, text) in (1..).zip(src.lines()) {
+        let text = text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        let Some(kind) = text
+            .strip_prefix('S')
+            .and_then(|rest| rest.chars().next())
+            .and_then(|kind| kind.to_digit(10))
+        else {
+            return Err(Error::Format {
+                line,
+                what: "missing record type",
+            });
+        };
+        #[allow(clippy::cast_possible_truncation)]
+        let kind = kind as u8;
+        let bytes = decode(line, &text[2..])?;
+        // Check the record's framing
+        let Some(&len) = bytes.first() else {
+            return Err(Error::Format {
+                line,
+                what: "record too short",
+            });
+        };
+        if bytes.len() != usize::from(len) + 1 {
+            return Err(Error::Format {
+                line,
+                what: "byte count does not match record length",
+            });
+        }
+        let (body, found) = (&bytes[..bytes.len() - 1], bytes[bytes.len() - 1]);
+        let expected = !body.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte));
+        if expected != found {
+            return Err(Error::Checksum {
+                line,
+                expected,
+                found,
+            });
+        }
+        // Interpret the record
+        let width = match kind {
+            0 | 1 | 5 | 9 => 2,
+            2 | 6 | 8 => 3,
+            3 | 7 => 4,
+            _ => return Err(Error::Kind { line, kind }),
+        };
+        if body.len() < width + 1 {
+            return Err(Error::Format {
+                line,
+                what: "record too short",
+            });
+        }
+        let addr = address(&body[1..=width]);
+        let data = &body[width + 1..];
+        match kind {
+            // Header
+            0 => (),
+            // Data
+            1..=3 => {
+                count += 1;
+                if !data.is_empty() {
+                    image.push(Segment {
+                        addr,
+                        data: data.to_vec(),
+                        line: Some(line),
+                    });
+                }
+            }
+            // Record count
+            5 | 6 => {
+                if addr != count {
+                    return Err(Error::Format {
+                        line,
+                        what: "record count does not match data records",
+                    });
+                }
+            }
+            // Termination
+            _ => {
+                image.set_entry(Some(addr));
+                break;
+            }
+        }
+    }
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_works() {
+        let image = parse(
+            "S00600004844521B\n\
+             S107001001020304DE\n\
+             S2060100000102F5\n\
+             S5030002FA\n\
+             S9030123D8\n",
+        )
+        .unwrap();
+        assert_eq!(
+            image.segments(),
+            [
+                Segment {
+                    addr: 0x0010,
+                    data: vec![0x01, 0x02, 0x03, 0x04],
+                    line: Some(2),
+                },
+                Segment {
+                    addr: 0x10000,
+                    data: vec![0x01, 0x02],
+                    line: Some(3),
+                },
+            ]
+        );
+        assert_eq!(image.entry(), Some(0x0123));
+    }
+
+    #[test]
+    fn parse_checksum_fails() {
+        assert!(matches!(
+            parse("S107001001020304DF"),
+            Err(Error::Checksum {
+                line: 1,
+                expected: 0xde,
+                found: 0xdf,
+            })
+        ));
+    }
+
+    #[test]
+    fn parse_format_fails() {
+        assert!(matches!(
+            parse("X1070010010203049E"),
+            Err(Error::Format { line: 1, .. })
+        ));
+        assert!(matches!(
+            parse("S1080010010203049D"),
+            Err(Error::Format { line: 1, .. })
+        ));
+        assert!(matches!(
+            parse("S107001001020304DE\nS5030002FA"),
+            Err(Error::Format { line: 2, .. })
+        ));
+        assert!(matches!(
+            parse("S4030000FC"),
+            Err(Error::Kind { line: 1, kind: 4 })
+        ));
+    }
+}
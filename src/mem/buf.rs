@@ -0,0 +1,161 @@
+use std::ops::RangeInclusive;
+
+use super::dirty::Dirty;
+use super::{bytes, bytes_mut};
+use crate::arch::Value;
+#[cfg(feature = "state")]
+use crate::state;
+
+/// Memory buffer.
+///
+/// Provides the storage and dirty tracking shared by all memory models,
+/// regardless of whether their size is known at compile time.
+#[derive(Debug, Default)]
+pub(super) struct Buffer<V>
+where
+    V: Value,
+{
+    data: Box<[V]>,
+    dirty: Option<Dirty>,
+}
+
+impl<V> Buffer<V>
+where
+    V: Value,
+{
+    /// Constructs a new, empty `Buffer` of the provided length.
+    pub(super) fn new(len: usize) -> Self {
+        Self::from(vec![V::default(); len].into_boxed_slice())
+    }
+
+    /// Enables dirty tracking, using pages of `1 << bits` elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if pages of `1 << bits` elements cannot be represented.
+    pub(super) fn track(&mut self, bits: u32) {
+        self.dirty = Some(Dirty::new(bits, self.data.len()));
+    }
+
+    /// Disables dirty tracking.
+    pub(super) fn untrack(&mut self) {
+        self.dirty = None;
+    }
+
+    /// Checks if dirty tracking is enabled.
+    pub(super) fn tracking(&self) -> bool {
+        self.dirty.is_some()
+    }
+
+    /// Checks if the page containing an index is dirty.
+    pub(super) fn is_dirty(&self, index: usize) -> bool {
+        self.dirty
+            .as_ref()
+            .is_some_and(|dirty| dirty.is_dirty(index))
+    }
+
+    /// Returns the ranges of all dirty pages.
+    pub(super) fn dirty(&self) -> Vec<RangeInclusive<usize>> {
+        self.dirty.as_ref().map(Dirty::ranges).unwrap_or_default()
+    }
+
+    /// Marks all pages as clean.
+    pub(super) fn clean(&mut self) {
+        if let Some(dirty) = &mut self.dirty {
+            dirty.clear();
+        }
+    }
+
+    /// Gets the value at an index.
+    pub(super) fn get(&self, index: usize) -> Option<V> {
+        self.data.get(index).copied()
+    }
+
+    /// Sets the value at an index, returning `None` if out of bounds.
+    pub(super) fn set(&mut self, index: usize, value: V) -> Option<()> {
+        *self.data.get_mut(index)? = value;
+        if let Some(dirty) = &mut self.dirty {
+            dirty.mark(index);
+        }
+        Some(())
+    }
+
+    /// Reads a contiguous block starting at an index, returning `None` if out
+    /// of bounds.
+    pub(super) fn read_block(&self, index: usize, buf: &mut [V]) -> Option<()> {
+        let end = index.checked_add(buf.len())?;
+        buf.copy_from_slice(self.data.get(index..end)?);
+        Some(())
+    }
+
+    /// Writes a contiguous block starting at an index, returning `None` if
+    /// out of bounds.
+    pub(super) fn write_block(&mut self, index: usize, buf: &[V]) -> Option<()> {
+        let end = index.checked_add(buf.len())?;
+        self.data.get_mut(index..end)?.copy_from_slice(buf);
+        if let Some(dirty) = &mut self.dirty {
+            dirty.mark_block(index, buf.len());
+        }
+        Some(())
+    }
+
+    /// Resets all values to their default.
+    pub(super) fn reset(&mut self) {
+        self.as_mut_slice().fill(V::default());
+    }
+
+    /// Views the buffer as a slice.
+    pub(super) fn as_slice(&self) -> &[V] {
+        &self.data
+    }
+
+    /// Mutably views the buffer as a slice.
+    ///
+    /// As writes through the slice cannot be observed, if dirty tracking is
+    /// enabled all pages are conservatively marked as dirty.
+    pub(super) fn as_mut_slice(&mut self) -> &mut [V] {
+        if let Some(dirty) = &mut self.dirty {
+            dirty.mark_all();
+        }
+        &mut self.data
+    }
+
+    /// Views the buffer as raw bytes, in native byte order.
+    pub(super) fn as_bytes(&self) -> &[u8] {
+        bytes(&self.data)
+    }
+
+    /// Mutably views the buffer as raw bytes, in native byte order.
+    ///
+    /// See [`Buffer::as_mut_slice`].
+    pub(super) fn as_bytes_mut(&mut self) -> &mut [u8] {
+        bytes_mut(self.as_mut_slice())
+    }
+
+    /// Saves the contents of the buffer.
+    #[cfg(feature = "state")]
+    pub(super) fn save_state(&self, state: &mut state::Writer) {
+        state.values(&self.data);
+    }
+
+    /// Loads the contents of the buffer.
+    ///
+    /// If dirty tracking is enabled, all pages are marked as dirty.
+    #[cfg(feature = "state")]
+    pub(super) fn load_state(&mut self, state: &mut state::Reader) -> Result<(), state::Error> {
+        state.values(&mut self.data)?;
+        if let Some(dirty) = &mut self.dirty {
+            dirty.mark_all();
+        }
+        Ok(())
+    }
+}
+
+impl<V> From<Box<[V]>> for Buffer<V>
+where
+    V: Value,
+{
+    fn from(data: Box<[V]>) -> Self {
+        Self { data, dirty: None }
+    }
+}
@@ -0,0 +1,122 @@
+use std::ops::RangeInclusive;
+
+/// Bits per bitmap word.
+const WORD: usize = u64::BITS as usize;
+
+/// Dirty page bitmap.
+///
+/// Tracks which pages of a memory have been written, where each page spans
+/// `1 << bits` elements.
+#[derive(Clone, Debug)]
+pub(super) struct Dirty {
+    bits: u32,
+    len: usize,
+    map: Vec<u64>,
+}
+
+impl Dirty {
+    /// Constructs a new, clean `Dirty` bitmap over `len` elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if pages of `1 << bits` elements cannot be represented.
+    pub(super) fn new(bits: u32, len: usize) -> Self {
+        assert!(bits < usize::BITS, "page size too large: 1 << {bits}");
+        let pages = len.div_ceil(1 << bits);
+        Self {
+            bits,
+            len,
+            map: vec![0; pages.div_ceil(WORD)],
+        }
+    }
+
+    /// Marks the page containing an index as dirty.
+    pub(super) fn mark(&mut self, index: usize) {
+        let page = index >> self.bits;
+        self.map[page / WORD] |= 1 << (page % WORD);
+    }
+
+    /// Marks all pages overlapping a block of `len` elements as dirty.
+    pub(super) fn mark_block(&mut self, index: usize, len: usize) {
+        if len == 0 {
+            return;
+        }
+        for page in (index >> self.bits)..=((index + len - 1) >> self.bits) {
+            self.map[page / WORD] |= 1 << (page % WORD);
+        }
+    }
+
+    /// Marks all pages as dirty.
+    pub(super) fn mark_all(&mut self) {
+        self.mark_block(0, self.len);
+    }
+
+    /// Checks if the page containing an index is dirty.
+    pub(super) fn is_dirty(&self, index: usize) -> bool {
+        let page = index >> self.bits;
+        self.map
+            .get(page / WORD)
+            .is_some_and(|word| word & (1 << (page % WORD)) != 0)
+    }
+
+    /// Marks all pages as clean.
+    pub(super) fn clear(&mut self) {
+        self.map.fill(0);
+    }
+
+    /// Returns the element ranges spanned by dirty pages.
+    ///
+    /// Adjacent dirty pages are coalesced into a single range.
+    pub(super) fn ranges(&self) -> Vec<RangeInclusive<usize>> {
+        let size = 1 << self.bits;
+        let mut ranges: Vec<RangeInclusive<usize>> = Vec::new();
+        for (idx, &word) in self.map.iter().enumerate() {
+            let mut word = word;
+            while word != 0 {
+                let page = idx * WORD + word.trailing_zeros() as usize;
+                word &= word - 1;
+                let start = page * size;
+                let end = (start + size).min(self.len) - 1;
+                match ranges.last_mut() {
+                    Some(last) if last.end() + 1 == start => *last = *last.start()..=end,
+                    _ => ranges.push(start..=end),
+                }
+            }
+        }
+        ranges
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mark_works() {
+        let mut dirty = Dirty::new(4, 0x100);
+        dirty.mark(0x12);
+        dirty.mark(0x2f);
+        dirty.mark(0x30);
+        assert!(dirty.is_dirty(0x1f));
+        assert!(!dirty.is_dirty(0x40));
+        assert_eq!(dirty.ranges(), [0x10..=0x3f]);
+        dirty.clear();
+        assert!(dirty.ranges().is_empty());
+    }
+
+    #[test]
+    fn mark_block_works() {
+        let mut dirty = Dirty::new(2, 0x102);
+        dirty.mark_block(0x0f, 2);
+        dirty.mark_block(0xff, 3);
+        assert_eq!(dirty.ranges(), [0x0c..=0x13, 0xfc..=0x101]);
+        dirty.mark_all();
+        assert_eq!(dirty.ranges(), [0x00..=0x101]);
+    }
+
+    #[test]
+    #[should_panic(expected = "page size too large")]
+    fn new_bits_panics() {
+        let _ = Dirty::new(usize::BITS, 0x100);
+    }
+}
@@ -4,14 +4,40 @@
 //!
 //! The [`Ram`] and [`Rom`] memory models work similarly to one another, with
 //! the obvious exception that `Rom` panics on writes. As both implement
-//! [`Deref`](std::ops::Deref) into a `[u8]`, all expected [`std::slice`]
-//! functions are available.
+//! [`Deref`](std::ops::Deref) into a `[V]`, all expected [`std::slice`]
+//! functions are available. For wider values, the underlying storage can also
+//! be viewed as raw bytes (e.g. [`Ram::as_bytes`]), allowing frontends to blit
+//! video memory or persist save data without per-element reads.
 //!
 //! Additionally, both models implement [`Device`](crate::dev::Device), allowing
 //! them to be mapped to another address space.
+//!
+//! Where the size of a memory is only known at runtime, the [`DynRam`] and
+//! [`DynRom`] models can be used instead.
 
+mod buf;
+mod dirty;
 mod ram;
 mod rom;
 
-pub use self::ram::Ram;
-pub use self::rom::Rom;
+pub use self::ram::{DynRam, Ram};
+pub use self::rom::{DynRom, Rom};
+
+use crate::arch::Value;
+
+/// Views a slice of values as its underlying bytes, in native byte order.
+fn bytes<V: Value>(data: &[V]) -> &[u8] {
+    // SAFETY: `Value` is sealed (see `arch::value::Sealed`), and so is only
+    //         ever implemented for primitive integers, which have no padding
+    //         and no invalid bit patterns. Any alignment is valid for `u8`,
+    //         and the length covers exactly the same allocation.
+    unsafe { std::slice::from_raw_parts(data.as_ptr().cast(), std::mem::size_of_val(data)) }
+}
+
+/// Mutably views a slice of values as its underlying bytes, in native byte
+/// order.
+fn bytes_mut<V: Value>(data: &mut [V]) -> &mut [u8] {
+    // SAFETY: See `bytes`. Additionally, as every bit pattern is a valid
+    //         `Value`, arbitrary byte writes cannot produce an invalid value.
+    unsafe { std::slice::from_raw_parts_mut(data.as_mut_ptr().cast(), std::mem::size_of_val(data)) }
+}
@@ -1,12 +1,32 @@
+use std::io::{self, Read};
+use std::ops::{Deref, DerefMut, RangeInclusive};
+
 use thiserror::Error;
 
-use crate::arch::{Address, TryAddress, Value};
+use super::buf::Buffer;
+use crate::arch::{Address, Context, TryAddress, Value};
 use crate::blk::Block;
-use crate::dev::Device;
+use crate::dev::{self, Device};
+#[cfg(feature = "state")]
+use crate::state;
 
 /// Random-access memory model.
+///
+/// # Dirty tracking
+///
+/// Writes to a `Ram` can optionally be tracked at the granularity of pages,
+/// allowing consumers such as memory viewers to determine which regions have
+/// changed since last [cleaned](Ram::clean). Tracking is disabled by default,
+/// and is enabled with [`Ram::track`].
+///
+/// Only writes through the memory's own interfaces can be attributed to a
+/// page. Mutably borrowing its contents, whether through
+/// [`DerefMut`](std::ops::DerefMut), [`AsMut`], or [`Ram::as_bytes_mut`],
+/// conservatively marks every page as dirty, even if nothing is written. Where
+/// precise tracking matters, prefer [`Address::write`] or
+/// [`Device::write_block`].
 #[derive(Debug)]
-pub struct Ram<V, const N: usize>(Box<[V; N]>)
+pub struct Ram<V, const N: usize>(Buffer<V>)
 where
     V: Value;
 
@@ -21,85 +41,334 @@ where
     }
 }
 
-impl<Idx, V, const N: usize> Address<Idx, V> for Ram<V, N>
+impl<V, const N: usize> Default for Ram<V, N>
 where
-    Idx: Value,
     V: Value,
-    usize: From<Idx>,
 {
-    fn read(&self, index: Idx) -> V {
-        self.try_read(index).unwrap()
+    fn default() -> Self {
+        Self(Buffer::new(N))
     }
+}
 
-    fn write(&mut self, index: Idx, value: V) {
-        self.try_write(index, value).unwrap();
+impl<V, const N: usize> From<&[V; N]> for Ram<V, N>
+where
+    V: Value,
+{
+    fn from(arr: &[V; N]) -> Self {
+        Self(Buffer::from(Box::from(&arr[..])))
     }
 }
 
-impl<Idx, V, const N: usize> TryAddress<Idx, V> for Ram<V, N>
+/// Dynamically-sized random-access memory model.
+///
+/// # Usage
+///
+/// Unlike [`Ram`], the size of a `DynRam` is determined at runtime. This is
+/// useful when the size of a memory is only known after parsing a header, such
+/// as for a cartridge's save RAM.
+///
+/// See [`Ram`] for details on dirty tracking.
+#[derive(Debug, Default)]
+pub struct DynRam<V>(Buffer<V>)
+where
+    V: Value;
+
+impl<V> DynRam<V>
 where
-    Idx: Value,
     V: Value,
-    usize: From<Idx>,
 {
-    type Error = Error<Idx>;
+    /// Constructs a new, empty `DynRam` of the provided length.
+    #[must_use]
+    pub fn new(len: usize) -> Self {
+        Self(Buffer::new(len))
+    }
 
-    fn try_read(&self, index: Idx) -> Result<V, Self::Error> {
-        self.0
-            .get(usize::from(index))
-            .copied()
-            .ok_or(Error::Bounds(index))
+    /// Returns the length of the memory.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.as_slice().len()
     }
 
-    fn try_write(&mut self, index: Idx, value: V) -> Result<(), Self::Error> {
-        self.0
-            .get_mut(usize::from(index))
-            .map(|v| *v = value)
-            .ok_or(Error::Bounds(index))
+    /// Checks if the memory has a length of zero.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.as_slice().is_empty()
+    }
+}
+
+impl DynRam<u8> {
+    /// Constructs a new `DynRam` from the contents of a reader.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the reader could not be read to completion.
+    pub fn from_reader(mut reader: impl Read) -> io::Result<Self> {
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf)?;
+        Ok(Self::from(buf))
     }
 }
 
-impl<V, const N: usize> Block for Ram<V, N>
+impl<V> From<&[V]> for DynRam<V>
 where
     V: Value,
 {
-    fn reset(&mut self) {
-        std::mem::take(self);
+    fn from(data: &[V]) -> Self {
+        Self::from(Box::from(data))
     }
 }
 
-impl<V, const N: usize> Default for Ram<V, N>
+impl<V> From<Box<[V]>> for DynRam<V>
 where
     V: Value,
 {
-    fn default() -> Self {
-        Self(
-            vec![Default::default(); N]
-                .into_boxed_slice()
-                .try_into()
-                .unwrap(),
-        )
+    fn from(data: Box<[V]>) -> Self {
+        Self(Buffer::from(data))
     }
 }
 
-impl<Idx, V, const N: usize> Device<Idx, V> for Ram<V, N>
+impl<V> From<Vec<V>> for DynRam<V>
 where
-    Idx: Value,
     V: Value,
-    usize: From<Idx>,
 {
+    fn from(data: Vec<V>) -> Self {
+        Self::from(data.into_boxed_slice())
+    }
 }
 
-impl<V, const N: usize> From<&[V; N]> for Ram<V, N>
+impl<V, const N: usize> From<Ram<V, N>> for DynRam<V>
 where
     V: Value,
 {
-    fn from(arr: &[V; N]) -> Self {
-        Self(Vec::from(&arr[..]).into_boxed_slice().try_into().unwrap())
+    fn from(ram: Ram<V, N>) -> Self {
+        Self(ram.0)
     }
 }
 
-/// A type specifying general categories of [`Ram`] error.
+/// Implements the interface shared by [`Ram`] and [`DynRam`] over their
+/// [`Buffer`].
+macro_rules! impl_ram {
+    ($ty:ident $(<const $n:ident>)?) => {
+        impl<V $(, const $n: usize)?> $ty<V $(, $n)?>
+        where
+            V: Value,
+        {
+            /// Enables dirty tracking, using pages of `1 << bits` elements.
+            ///
+            /// Any previously tracked writes are discarded.
+            ///
+            /// # Panics
+            ///
+            /// Panics if pages of `1 << bits` elements cannot be represented.
+            pub fn track(&mut self, bits: u32) {
+                self.0.track(bits);
+            }
+
+            /// Disables dirty tracking.
+            pub fn untrack(&mut self) {
+                self.0.untrack();
+            }
+
+            /// Checks if dirty tracking is enabled.
+            #[must_use]
+            pub fn tracking(&self) -> bool {
+                self.0.tracking()
+            }
+
+            /// Checks if the page containing an index has been written since
+            /// last cleaned.
+            ///
+            /// Always returns `false` if dirty tracking is disabled.
+            #[must_use]
+            pub fn is_dirty(&self, index: usize) -> bool {
+                self.0.is_dirty(index)
+            }
+
+            /// Returns the ranges of all pages written since last cleaned.
+            ///
+            /// Always returns an empty list if dirty tracking is disabled.
+            #[must_use]
+            pub fn dirty(&self) -> Vec<RangeInclusive<usize>> {
+                self.0.dirty()
+            }
+
+            /// Marks all pages as clean.
+            pub fn clean(&mut self) {
+                self.0.clean();
+            }
+
+            /// Views the memory as raw bytes, in native byte order.
+            #[must_use]
+            pub fn as_bytes(&self) -> &[u8] {
+                self.0.as_bytes()
+            }
+
+            /// Mutably views the memory as raw bytes, in native byte order.
+            ///
+            /// If dirty tracking is enabled, all pages are marked as dirty.
+            pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+                self.0.as_bytes_mut()
+            }
+        }
+
+        impl<Idx, V $(, const $n: usize)?> Address<Idx, V> for $ty<V $(, $n)?>
+        where
+            Idx: Value,
+            V: Value,
+            usize: From<Idx>,
+        {
+            fn read(&self, index: Idx) -> V {
+                self.try_read(index).unwrap()
+            }
+
+            fn write(&mut self, index: Idx, value: V) {
+                self.try_write(index, value).unwrap();
+            }
+        }
+
+        impl<Idx, V $(, const $n: usize)?> TryAddress<Idx, V> for $ty<V $(, $n)?>
+        where
+            Idx: Value,
+            V: Value,
+            usize: From<Idx>,
+        {
+            type Error = Error<Idx>;
+
+            fn try_read(&self, index: Idx) -> Result<V, Self::Error> {
+                self.0.get(usize::from(index)).ok_or(Error::Bounds(index))
+            }
+
+            fn try_write(&mut self, index: Idx, value: V) -> Result<(), Self::Error> {
+                self.0
+                    .set(usize::from(index), value)
+                    .ok_or(Error::Bounds(index))
+            }
+        }
+
+        impl<V $(, const $n: usize)?> Block for $ty<V $(, $n)?>
+        where
+            V: Value,
+        {
+            fn reset(&mut self) {
+                self.0.reset();
+            }
+
+            #[cfg(feature = "state")]
+            fn save_state(&self, state: &mut state::Writer) {
+                state.section("ram", 1, |state| self.0.save_state(state));
+            }
+
+            #[cfg(feature = "state")]
+            fn load_state(&mut self, state: &mut state::Reader) -> Result<(), state::Error> {
+                state.section("ram", 1, |state, _| self.0.load_state(state))
+            }
+        }
+
+        impl<V $(, const $n: usize)?> AsMut<[V]> for $ty<V $(, $n)?>
+        where
+            V: Value,
+        {
+            fn as_mut(&mut self) -> &mut [V] {
+                self
+            }
+        }
+
+        impl<V $(, const $n: usize)?> AsRef<[V]> for $ty<V $(, $n)?>
+        where
+            V: Value,
+        {
+            fn as_ref(&self) -> &[V] {
+                self
+            }
+        }
+
+        impl<V $(, const $n: usize)?> Deref for $ty<V $(, $n)?>
+        where
+            V: Value,
+        {
+            type Target = [V];
+
+            fn deref(&self) -> &Self::Target {
+                self.0.as_slice()
+            }
+        }
+
+        impl<V $(, const $n: usize)?> DerefMut for $ty<V $(, $n)?>
+        where
+            V: Value,
+        {
+            /// Mutably dereferences the memory.
+            ///
+            /// As writes through the slice cannot be observed, if dirty
+            /// tracking is enabled all pages are conservatively marked as
+            /// dirty.
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                self.0.as_mut_slice()
+            }
+        }
+
+        impl<Idx, V $(, const $n: usize)?> Device<Idx, V> for $ty<V $(, $n)?>
+        where
+            Idx: Value,
+            V: Value,
+            usize: From<Idx>,
+        {
+            fn try_read_dyn(&self, index: Idx) -> Result<V, dev::Error> {
+                self.try_read(index).map_err(Into::into)
+            }
+
+            fn try_write_dyn(&mut self, index: Idx, value: V) -> Result<(), dev::Error> {
+                self.try_write(index, value).map_err(Into::into)
+            }
+
+            /// Fallibly reads within the provided context.
+            ///
+            /// Reads have no side effects, so [debug](crate::arch::Access::Debug) reads
+            /// are bounds checked like any other.
+            fn try_read_ctx(&self, index: Idx, _: Context) -> Result<V, dev::Error> {
+                self.try_read_dyn(index)
+            }
+
+            /// Fallibly writes within the provided context.
+            ///
+            /// Writes have no side effects, so [debug](crate::arch::Access::Debug) writes
+            /// are bounds checked like any other.
+            fn try_write_ctx(
+                &mut self,
+                index: Idx,
+                value: V,
+                _: Context,
+            ) -> Result<(), dev::Error> {
+                self.try_write_dyn(index, value)
+            }
+
+            fn try_read_block(&self, index: Idx, buf: &mut [V]) -> Result<(), dev::Error> {
+                self.0
+                    .read_block(usize::from(index), buf)
+                    .ok_or_else(|| Error::Bounds(index).into())
+            }
+
+            fn try_write_block(&mut self, index: Idx, buf: &[V]) -> Result<(), dev::Error> {
+                self.0
+                    .write_block(usize::from(index), buf)
+                    .ok_or_else(|| Error::Bounds(index).into())
+            }
+
+            fn dirty(&self) -> Vec<RangeInclusive<usize>> {
+                self.0.dirty()
+            }
+
+            fn clean(&mut self) {
+                self.0.clean();
+            }
+        }
+    };
+}
+
+impl_ram!(Ram<const N>);
+impl_ram!(DynRam);
+
+/// A type specifying general categories of [`Ram`] and [`DynRam`] error.
 #[derive(Debug, Error)]
 pub enum Error<Idx: Value> {
     #[error("index out of bounds: {0:?}")]
@@ -115,7 +384,7 @@ mod tests {
     #[test]
     fn new_works() {
         let ram = Ram::<u8, 0x100>::new();
-        assert!(ram.0.iter().all(|&byte| byte == 0));
+        assert!(ram.iter().all(|&byte| byte == 0));
     }
 
     #[test]
@@ -124,12 +393,12 @@ mod tests {
 
         let arr = [0; N];
         let ram = Ram::from(&arr);
-        assert_eq!(*ram.0, arr);
+        assert_eq!(*ram, arr);
 
         let vec: Vec<u8> = (0..N).map(|x| x as u8).collect();
         let buf: [u8; N] = vec.try_into().unwrap();
         let ram = Ram::from(&buf);
-        assert_eq!(*ram.0, buf);
+        assert_eq!(*ram, buf);
     }
 
     #[test]
@@ -139,4 +408,84 @@ mod tests {
         ram.write(0x0usize, 0xaa);
         assert_eq!(ram.read(0x0usize), 0xaa);
     }
+
+    #[test]
+    fn device_block_works() {
+        let mut ram: Ram<u8, 0x100> = Ram::new();
+        ram.write_block(0x10usize, &[0xaa; 0x20]);
+        let mut buf = [0; 0x40];
+        ram.read_block(0x00usize, &mut buf);
+        assert!(buf[..0x10].iter().all(|&byte| byte == 0x00));
+        assert!(buf[0x10..0x30].iter().all(|&byte| byte == 0xaa));
+        assert!(buf[0x30..].iter().all(|&byte| byte == 0x00));
+    }
+
+    #[test]
+    fn deref_works() {
+        let mut ram: Ram<u8, 0x100> = Ram::new();
+        ram[0x10..0x20].fill(0xaa);
+        assert_eq!(ram.read(0x1fusize), 0xaa);
+        assert_eq!(ram.as_ref().len(), 0x100);
+        // Mutable access conservatively dirties all pages
+        ram.track(4);
+        ram.as_mut()[0x00] = 0xbb;
+        assert_eq!(ram.dirty(), [0x00..=0xff]);
+    }
+
+    #[test]
+    fn as_bytes_works() {
+        let mut ram: Ram<u16, 0x10> = Ram::new();
+        ram.write(0x1usize, 0xaabb);
+        assert_eq!(ram.as_bytes().len(), 0x20);
+        assert_eq!(ram.as_bytes()[2..4], 0xaabbu16.to_ne_bytes());
+        ram.as_bytes_mut()[4..6].copy_from_slice(&0xccddu16.to_ne_bytes());
+        assert_eq!(ram.read(0x2usize), 0xccdd);
+        let mut ram = DynRam::<u32>::new(4);
+        ram.track(1);
+        ram.as_bytes_mut().fill(0xff);
+        assert_eq!(ram.read(0x3usize), u32::MAX);
+        assert_eq!(ram.dirty(), [0..=3]);
+    }
+
+    #[test]
+    fn dirty_works() {
+        let mut ram: Ram<u8, 0x100> = Ram::new();
+        ram.write(0x00usize, 0xaa);
+        assert!(ram.dirty().is_empty());
+        ram.track(4);
+        ram.write(0x12usize, 0xaa);
+        ram.write_block(0x3cusize, &[0xbb; 0x08]);
+        assert!(ram.is_dirty(0x1f));
+        assert!(!ram.is_dirty(0x20));
+        assert_eq!(ram.dirty(), [0x10..=0x1f, 0x30..=0x4f]);
+        ram.clean();
+        assert!(ram.dirty().is_empty());
+        ram.reset();
+        assert_eq!(ram.dirty(), [0x00..=0xff]);
+    }
+
+    #[test]
+    fn dyn_ram_works() {
+        let mut ram = DynRam::<u8>::new(0x300);
+        assert_eq!(ram.len(), 0x300);
+        ram.write(0x2ffusize, 0xaa);
+        assert_eq!(ram.read(0x2ffusize), 0xaa);
+        assert!(matches!(
+            ram.try_read(0x300usize),
+            Err(Error::Bounds(0x300))
+        ));
+        ram.track(8);
+        ram.write_block(0x0ffusize, &[0xbb; 2]);
+        assert_eq!(ram.dirty(), [0x000..=0x1ff]);
+    }
+
+    #[test]
+    fn dyn_ram_from_works() {
+        let ram = DynRam::from(&[0x00u8, 0x11, 0x22][..]);
+        assert_eq!(ram.read(0x2usize), 0x22);
+        let ram = DynRam::from_reader(&[0xaa; 0x10][..]).unwrap();
+        assert_eq!(ram.len(), 0x10);
+        let ram = DynRam::from(Ram::from(&[0xbbu8; 0x20]));
+        assert_eq!(ram.read(0x1fusize), 0xbb);
+    }
 }
@@ -1,8 +1,14 @@
+use std::io::{self, Read};
+use std::ops::Deref;
+
 use thiserror::Error;
 
-use crate::arch::{Address, TryAddress, Value};
+use super::buf::Buffer;
+use crate::arch::{Access, Address, Context, TryAddress, Value};
 use crate::blk::Block;
-use crate::dev::Device;
+use crate::dev::{self, Device};
+#[cfg(feature = "state")]
+use crate::state;
 
 /// Read-only memory model.
 ///
@@ -10,7 +16,7 @@ use crate::dev::Device;
 ///
 /// Panics on [`Address::write`].
 #[derive(Debug)]
-pub struct Rom<V, const N: usize>(Box<[V; N]>)
+pub struct Rom<V, const N: usize>(Buffer<V>)
 where
     V: Value;
 
@@ -25,82 +31,284 @@ where
     }
 }
 
-impl<Idx, V, const N: usize> Address<Idx, V> for Rom<V, N>
+impl<V, const N: usize> Default for Rom<V, N>
 where
-    Idx: Value,
     V: Value,
-    usize: From<Idx>,
 {
-    fn read(&self, index: Idx) -> V {
-        self.try_read(index).unwrap()
+    fn default() -> Self {
+        Self(Buffer::new(N))
     }
+}
 
-    /// # Panics
-    ///
-    /// Panics when attempting to write to a [`Rom`].
-    fn write(&mut self, index: Idx, value: V) {
-        let err = self.try_write(index, value).unwrap_err();
-        panic!("`<Rom as Address>::write`: {err}");
+impl<V, const N: usize> From<&[V; N]> for Rom<V, N>
+where
+    V: Value,
+{
+    fn from(arr: &[V; N]) -> Self {
+        Self(Buffer::from(Box::from(&arr[..])))
     }
 }
 
-impl<Idx, V, const N: usize> TryAddress<Idx, V> for Rom<V, N>
+/// Dynamically-sized read-only memory model.
+///
+/// # Usage
+///
+/// Unlike [`Rom`], the size of a `DynRom` is determined at runtime. This is
+/// useful when the size of a memory is only known after parsing a header, such
+/// as for a cartridge's ROM.
+///
+/// # Panics
+///
+/// Panics on [`Address::write`].
+#[derive(Debug, Default)]
+pub struct DynRom<V>(Buffer<V>)
+where
+    V: Value;
+
+impl<V> DynRom<V>
 where
-    Idx: Value,
     V: Value,
-    usize: From<Idx>,
 {
-    type Error = Error<Idx>;
+    /// Constructs a new, empty `DynRom` of the provided length.
+    #[must_use]
+    pub fn new(len: usize) -> Self {
+        Self(Buffer::new(len))
+    }
 
-    fn try_read(&self, index: Idx) -> Result<V, Self::Error> {
-        self.0
-            .get(usize::from(index))
-            .copied()
-            .ok_or(Error::Bounds(index))
+    /// Returns the length of the memory.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.as_slice().len()
     }
 
-    fn try_write(&mut self, index: Idx, _: V) -> Result<(), Self::Error> {
-        match self.0.get(usize::from(index)) {
-            Some(_) => Err(Error::Write),
-            None => Err(Error::Bounds(index)),
-        }
+    /// Checks if the memory has a length of zero.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.as_slice().is_empty()
     }
 }
 
-impl<V, const N: usize> Block for Rom<V, N> where V: Value {}
+impl DynRom<u8> {
+    /// Constructs a new `DynRom` from the contents of a reader.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the reader could not be read to completion.
+    pub fn from_reader(mut reader: impl Read) -> io::Result<Self> {
+        let mut buf = Vec::new();
+        reader.read_to_end(&mut buf)?;
+        Ok(Self::from(buf))
+    }
+}
 
-impl<V, const N: usize> Default for Rom<V, N>
+impl<V> From<&[V]> for DynRom<V>
 where
     V: Value,
 {
-    fn default() -> Self {
-        Self(
-            vec![Default::default(); N]
-                .into_boxed_slice()
-                .try_into()
-                .unwrap(),
-        )
+    fn from(data: &[V]) -> Self {
+        Self::from(Box::from(data))
     }
 }
 
-impl<Idx, V, const N: usize> Device<Idx, V> for Rom<V, N>
+impl<V> From<Box<[V]>> for DynRom<V>
 where
-    Idx: Value,
     V: Value,
-    usize: From<Idx>,
 {
+    fn from(data: Box<[V]>) -> Self {
+        Self(Buffer::from(data))
+    }
 }
 
-impl<V, const N: usize> From<&[V; N]> for Rom<V, N>
+impl<V> From<Vec<V>> for DynRom<V>
 where
     V: Value,
 {
-    fn from(arr: &[V; N]) -> Self {
-        Self(Vec::from(&arr[..]).into_boxed_slice().try_into().unwrap())
+    fn from(data: Vec<V>) -> Self {
+        Self::from(data.into_boxed_slice())
     }
 }
 
-/// A type specifying general categories of [`Rom`] error.
+impl<V, const N: usize> From<Rom<V, N>> for DynRom<V>
+where
+    V: Value,
+{
+    fn from(rom: Rom<V, N>) -> Self {
+        Self(rom.0)
+    }
+}
+
+/// Implements the interface shared by [`Rom`] and [`DynRom`] over their
+/// [`Buffer`].
+macro_rules! impl_rom {
+    ($ty:ident $(<const $n:ident>)?) => {
+        impl<V $(, const $n: usize)?> $ty<V $(, $n)?>
+        where
+            V: Value,
+        {
+            /// Views the memory as raw bytes, in native byte order.
+            #[must_use]
+            pub fn as_bytes(&self) -> &[u8] {
+                self.0.as_bytes()
+            }
+        }
+
+        impl<Idx, V $(, const $n: usize)?> Address<Idx, V> for $ty<V $(, $n)?>
+        where
+            Idx: Value,
+            V: Value,
+            usize: From<Idx>,
+        {
+            fn read(&self, index: Idx) -> V {
+                self.try_read(index).unwrap()
+            }
+
+            /// # Panics
+            ///
+            #[doc = concat!("Panics when attempting to write to a [`", stringify!($ty), "`].")]
+            fn write(&mut self, index: Idx, value: V) {
+                let err = self.try_write(index, value).unwrap_err();
+                panic!("`<{} as Address>::write`: {err}", stringify!($ty));
+            }
+        }
+
+        impl<Idx, V $(, const $n: usize)?> TryAddress<Idx, V> for $ty<V $(, $n)?>
+        where
+            Idx: Value,
+            V: Value,
+            usize: From<Idx>,
+        {
+            type Error = Error<Idx>;
+
+            fn try_read(&self, index: Idx) -> Result<V, Self::Error> {
+                self.0.get(usize::from(index)).ok_or(Error::Bounds(index))
+            }
+
+            fn try_write(&mut self, index: Idx, _: V) -> Result<(), Self::Error> {
+                match self.0.get(usize::from(index)) {
+                    Some(_) => Err(Error::Write),
+                    None => Err(Error::Bounds(index)),
+                }
+            }
+        }
+
+        impl<V $(, const $n: usize)?> Block for $ty<V $(, $n)?>
+        where
+            V: Value,
+        {
+            #[cfg(feature = "state")]
+            fn save_state(&self, state: &mut state::Writer) {
+                state.section("rom", 1, |state| {
+                    // Contents are immutable, and so are not volatile
+                    if !state.volatile() {
+                        self.0.save_state(state);
+                    }
+                });
+            }
+
+            #[cfg(feature = "state")]
+            fn load_state(&mut self, state: &mut state::Reader) -> Result<(), state::Error> {
+                state.section("rom", 1, |state, _| {
+                    if state.volatile() {
+                        return Ok(());
+                    }
+                    self.0.load_state(state)
+                })
+            }
+        }
+
+        impl<V $(, const $n: usize)?> AsRef<[V]> for $ty<V $(, $n)?>
+        where
+            V: Value,
+        {
+            fn as_ref(&self) -> &[V] {
+                self
+            }
+        }
+
+        impl<V $(, const $n: usize)?> Deref for $ty<V $(, $n)?>
+        where
+            V: Value,
+        {
+            type Target = [V];
+
+            fn deref(&self) -> &Self::Target {
+                self.0.as_slice()
+            }
+        }
+
+        impl<Idx, V $(, const $n: usize)?> Device<Idx, V> for $ty<V $(, $n)?>
+        where
+            Idx: Value,
+            V: Value,
+            usize: From<Idx>,
+        {
+            fn try_read_dyn(&self, index: Idx) -> Result<V, dev::Error> {
+                self.try_read(index).map_err(Into::into)
+            }
+
+            fn try_write_dyn(&mut self, index: Idx, value: V) -> Result<(), dev::Error> {
+                self.try_write(index, value).map_err(Into::into)
+            }
+
+            fn try_read_block(&self, index: Idx, buf: &mut [V]) -> Result<(), dev::Error> {
+                self.0
+                    .read_block(usize::from(index), buf)
+                    .ok_or_else(|| Error::Bounds(index).into())
+            }
+
+            fn try_write_block(&mut self, index: Idx, buf: &[V]) -> Result<(), dev::Error> {
+                let end = usize::from(index).checked_add(buf.len());
+                match end.filter(|&end| end <= self.0.as_slice().len()) {
+                    Some(_) => Err(Error::<Idx>::Write.into()),
+                    None => Err(Error::Bounds(index).into()),
+                }
+            }
+
+            /// Fallibly reads within the provided context.
+            ///
+            /// Reads have no side effects, so [debug](Access::Debug) reads
+            /// are bounds checked like any other.
+            fn try_read_ctx(&self, index: Idx, _: Context) -> Result<V, dev::Error> {
+                self.try_read_dyn(index)
+            }
+
+            /// Fallibly writes within the provided context.
+            ///
+            /// [Debug](Access::Debug) writes patch the underlying data, as
+            /// with [`Device::poke`].
+            fn try_write_ctx(
+                &mut self,
+                index: Idx,
+                value: V,
+                ctx: Context,
+            ) -> Result<(), dev::Error> {
+                if ctx.access == Access::Debug {
+                    self.0
+                        .set(usize::from(index), value)
+                        .ok_or_else(|| Error::Bounds(index).into())
+                } else {
+                    self.try_write_dyn(index, value)
+                }
+            }
+
+            /// Pokes the underlying data, allowing the memory to be patched.
+            ///
+            /// # Panics
+            ///
+            /// Panics if the index is out of bounds.
+            fn poke(&mut self, index: Idx, value: V) {
+                self.0
+                    .set(usize::from(index), value)
+                    .expect("index out of bounds");
+            }
+        }
+    };
+}
+
+impl_rom!(Rom<const N>);
+impl_rom!(DynRom);
+
+/// A type specifying general categories of [`Rom`] and [`DynRom`] error.
 #[derive(Debug, Error)]
 pub enum Error<Idx: Value> {
     #[error("index out of bounds: {0:?}")]
@@ -117,7 +325,7 @@ mod tests {
     #[test]
     fn new_works() {
         let rom = Rom::<u8, 0x100>::new();
-        assert!(rom.0.iter().all(|&byte| byte == 0));
+        assert!(rom.iter().all(|&byte| byte == 0));
     }
 
     #[test]
@@ -126,12 +334,12 @@ mod tests {
 
         let arr = [0; N];
         let rom = Rom::from(&arr);
-        assert_eq!(*rom.0, arr);
+        assert_eq!(*rom, arr);
 
         let vec: Vec<u8> = (0..N).map(|x| x as u8).collect();
         let buf: [u8; N] = vec.try_into().unwrap();
         let rom = Rom::from(&buf);
-        assert_eq!(*rom.0, buf);
+        assert_eq!(*rom, buf);
     }
 
     #[test]
@@ -140,10 +348,67 @@ mod tests {
         assert_eq!(rom.read(0x0usize), 0xaa);
     }
 
+    #[test]
+    fn device_read_block_works() {
+        let rom = Rom::from(&[0x00, 0x11, 0x22, 0x33]);
+        let mut buf = [0; 2];
+        rom.read_block(1usize, &mut buf);
+        assert_eq!(buf, [0x11, 0x22]);
+    }
+
+    #[test]
+    fn probe_poke_works() {
+        let mut rom = Rom::from(&[0x00, 0x11, 0x22, 0x33]);
+        rom.poke(2usize, 0xaa);
+        assert_eq!(rom.peek(2usize), 0xaa);
+        assert_eq!(rom.read(2usize), 0xaa);
+    }
+
+    #[test]
+    fn debug_ctx_works() {
+        let mut rom = Rom::from(&[0x00, 0x11, 0x22, 0x33]);
+        let ctx = Context::new(Access::Debug, 0);
+        // Debug writes patch the memory
+        rom.try_write_ctx(2usize, 0xaa, ctx).unwrap();
+        assert_eq!(rom.try_read_ctx(2usize, ctx).unwrap(), 0xaa);
+        // Other writes are rejected
+        assert!(rom.try_write_ctx(2usize, 0xbb, Context::default()).is_err());
+        // Out of bounds accesses are errors rather than panics
+        assert!(rom.try_write_ctx(4usize, 0xaa, ctx).is_err());
+        assert!(rom.try_read_ctx(4usize, ctx).is_err());
+    }
+
+    #[test]
+    fn deref_works() {
+        let rom = Rom::from(&[0x00u8, 0x11, 0x22, 0x33]);
+        assert_eq!(rom[1..3], [0x11, 0x22]);
+        assert_eq!(rom.iter().position(|&byte| byte == 0x33), Some(3));
+        let rom = DynRom::from(vec![0x0011u16, 0x2233]);
+        assert_eq!(rom.as_bytes().len(), 4);
+        assert_eq!(rom.as_bytes()[..2], 0x0011u16.to_ne_bytes());
+    }
+
     #[test]
     #[should_panic]
     fn address_write_panics() {
         let mut rom = Rom::from(&[0xaa]);
         rom.write(0x0usize, 0xaa);
     }
+
+    #[test]
+    fn dyn_rom_works() {
+        let rom = DynRom::from(vec![0x00u8, 0x11, 0x22, 0x33]);
+        assert_eq!(rom.len(), 4);
+        assert_eq!(rom.read(0x1usize), 0x11);
+        assert!(matches!(rom.try_read(0x4usize), Err(Error::Bounds(0x4))));
+        let rom = DynRom::from_reader(&[0xaa; 0x10][..]).unwrap();
+        assert_eq!(rom.read(0xfusize), 0xaa);
+    }
+
+    #[test]
+    #[should_panic(expected = "unsupported operation: write")]
+    fn dyn_rom_address_write_panics() {
+        let mut rom = DynRom::<u8>::new(1);
+        rom.write(0x0usize, 0xaa);
+    }
 }
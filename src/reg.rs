@@ -21,6 +21,8 @@ use std::fmt::Debug;
 use crate::arch::{Address, Cell, Value};
 use crate::blk::Block;
 use crate::dev::Device;
+#[cfg(feature = "state")]
+use crate::state;
 
 /// Register model.
 #[derive(Debug, Default)]
@@ -73,6 +75,19 @@ where
     fn reset(&mut self) {
         std::mem::take(self);
     }
+
+    #[cfg(feature = "state")]
+    fn save_state(&self, state: &mut state::Writer) {
+        state.section("reg", 1, |state| state.value(self.0));
+    }
+
+    #[cfg(feature = "state")]
+    fn load_state(&mut self, state: &mut state::Reader) -> Result<(), state::Error> {
+        state.section("reg", 1, |state, _| {
+            self.0 = state.value()?;
+            Ok(())
+        })
+    }
 }
 
 impl<V> From<V> for Register<V>
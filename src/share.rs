@@ -1,12 +1,15 @@
 use std::cell::{Ref, RefCell, RefMut};
+use std::ops::RangeInclusive;
 use std::rc::Rc;
 
-use crate::arch::{Address, Cell, Location, TryAddress, Value};
+use crate::arch::{Address, Cell, Context, Location, TryAddress, Value};
 use crate::blk::{Block, Linked};
-use crate::bus::{Bus, Mux, Range};
-use crate::dev::{Device, Dynamic};
+use crate::bus::{Bus, Handle, Mux, Range, Region};
+use crate::dev::{self, Device, Dynamic};
 use crate::fsm::Machine;
 use crate::pcb::Board;
+#[cfg(feature = "state")]
+use crate::state;
 
 /// Heap-allocated multi-access resource.
 #[derive(Debug, Default)]
@@ -88,6 +91,16 @@ where
     fn reset(&mut self) {
         self.0.reset();
     }
+
+    #[cfg(feature = "state")]
+    fn save_state(&self, state: &mut state::Writer) {
+        self.0.save_state(state);
+    }
+
+    #[cfg(feature = "state")]
+    fn load_state(&mut self, state: &mut state::Reader) -> Result<(), state::Error> {
+        self.0.load_state(state)
+    }
 }
 
 impl<T, Idx, V> Board<Idx, V> for Shared<T>
@@ -134,6 +147,45 @@ where
     Idx: Value,
     V: Value,
 {
+    fn try_read_dyn(&self, index: Idx) -> Result<V, dev::Error> {
+        self.0.try_read_dyn(index)
+    }
+
+    fn try_write_dyn(&mut self, index: Idx, value: V) -> Result<(), dev::Error> {
+        self.0.try_write_dyn(index, value)
+    }
+
+    fn try_read_ctx(&self, index: Idx, ctx: Context) -> Result<V, dev::Error> {
+        self.0.try_read_ctx(index, ctx)
+    }
+
+    fn try_write_ctx(&mut self, index: Idx, value: V, ctx: Context) -> Result<(), dev::Error> {
+        self.0.try_write_ctx(index, value, ctx)
+    }
+
+    fn try_read_block(&self, index: Idx, buf: &mut [V]) -> Result<(), dev::Error> {
+        self.0.try_read_block(index, buf)
+    }
+
+    fn try_write_block(&mut self, index: Idx, buf: &[V]) -> Result<(), dev::Error> {
+        self.0.try_write_block(index, buf)
+    }
+
+    fn dirty(&self) -> Vec<RangeInclusive<usize>> {
+        self.0.dirty()
+    }
+
+    fn clean(&mut self) {
+        self.0.clean();
+    }
+
+    fn peek(&self, index: Idx) -> V {
+        self.0.peek(index)
+    }
+
+    fn poke(&mut self, index: Idx, value: V) {
+        self.0.poke(index, value);
+    }
 }
 
 impl<T> From<T> for Shared<T>
@@ -209,13 +261,33 @@ where
         self.borrow().get(index)
     }
 
-    fn map(&mut self, range: Range<Idx>, dev: Dynamic<Idx, V>) {
-        self.borrow_mut().map(range, dev);
+    fn map(&mut self, range: Range<Idx>, dev: Dynamic<Idx, V>) -> Handle {
+        self.borrow_mut().map(range, dev)
     }
 
     fn unmap(&mut self, dev: &Dynamic<Idx, V>) -> Option<Dynamic<Idx, V>> {
         self.borrow_mut().unmap(dev)
     }
+
+    fn unmap_handle(&mut self, handle: Handle) -> Option<Dynamic<Idx, V>> {
+        self.borrow_mut().unmap_handle(handle)
+    }
+
+    fn unmap_range(&mut self, range: Range<Idx>) -> Vec<Dynamic<Idx, V>> {
+        self.borrow_mut().unmap_range(range)
+    }
+
+    fn remap(&mut self, handle: Handle, range: Range<Idx>) -> bool {
+        self.borrow_mut().remap(handle, range)
+    }
+
+    fn label(&mut self, handle: Handle, label: Option<String>) -> bool {
+        self.borrow_mut().label(handle, label)
+    }
+
+    fn regions(&self) -> Vec<Region<Idx, V>> {
+        self.borrow().regions()
+    }
 }
 
 /// Internal shared reference type.
@@ -260,6 +332,16 @@ where
     fn reset(&mut self) {
         self.borrow_mut().reset();
     }
+
+    #[cfg(feature = "state")]
+    fn save_state(&self, state: &mut state::Writer) {
+        self.borrow().save_state(state);
+    }
+
+    #[cfg(feature = "state")]
+    fn load_state(&mut self, state: &mut state::Reader) -> Result<(), state::Error> {
+        self.borrow_mut().load_state(state)
+    }
 }
 
 impl<T, Idx, V> Board<Idx, V> for Inner<T>
@@ -297,6 +379,45 @@ where
     Idx: Value,
     V: Value,
 {
+    fn try_read_dyn(&self, index: Idx) -> Result<V, dev::Error> {
+        self.borrow().try_read_dyn(index)
+    }
+
+    fn try_write_dyn(&mut self, index: Idx, value: V) -> Result<(), dev::Error> {
+        self.borrow_mut().try_write_dyn(index, value)
+    }
+
+    fn try_read_ctx(&self, index: Idx, ctx: Context) -> Result<V, dev::Error> {
+        self.borrow().try_read_ctx(index, ctx)
+    }
+
+    fn try_write_ctx(&mut self, index: Idx, value: V, ctx: Context) -> Result<(), dev::Error> {
+        self.borrow_mut().try_write_ctx(index, value, ctx)
+    }
+
+    fn try_read_block(&self, index: Idx, buf: &mut [V]) -> Result<(), dev::Error> {
+        self.borrow().try_read_block(index, buf)
+    }
+
+    fn try_write_block(&mut self, index: Idx, buf: &[V]) -> Result<(), dev::Error> {
+        self.borrow_mut().try_write_block(index, buf)
+    }
+
+    fn dirty(&self) -> Vec<RangeInclusive<usize>> {
+        self.borrow().dirty()
+    }
+
+    fn clean(&mut self) {
+        self.borrow_mut().clean();
+    }
+
+    fn peek(&self, index: Idx) -> V {
+        self.borrow().peek(index)
+    }
+
+    fn poke(&mut self, index: Idx, value: V) {
+        self.borrow_mut().poke(index, value);
+    }
 }
 
 impl<T, B> Linked<B> for Inner<T>
@@ -352,11 +473,31 @@ where
         self.borrow().get(index)
     }
 
-    fn map(&mut self, range: Range<Idx>, dev: Dynamic<Idx, V>) {
-        self.borrow_mut().map(range, dev);
+    fn map(&mut self, range: Range<Idx>, dev: Dynamic<Idx, V>) -> Handle {
+        self.borrow_mut().map(range, dev)
     }
 
     fn unmap(&mut self, dev: &Dynamic<Idx, V>) -> Option<Dynamic<Idx, V>> {
         self.borrow_mut().unmap(dev)
     }
+
+    fn unmap_handle(&mut self, handle: Handle) -> Option<Dynamic<Idx, V>> {
+        self.borrow_mut().unmap_handle(handle)
+    }
+
+    fn unmap_range(&mut self, range: Range<Idx>) -> Vec<Dynamic<Idx, V>> {
+        self.borrow_mut().unmap_range(range)
+    }
+
+    fn remap(&mut self, handle: Handle, range: Range<Idx>) -> bool {
+        self.borrow_mut().remap(handle, range)
+    }
+
+    fn label(&mut self, handle: Handle, label: Option<String>) -> bool {
+        self.borrow_mut().label(handle, label)
+    }
+
+    fn regions(&self) -> Vec<Region<Idx, V>> {
+        self.borrow().regions()
+    }
 }
@@ -0,0 +1,677 @@
+//! Save states.
+//!
+//! # Usage
+//!
+//! With the `state` feature enabled, every [`Block`] can record its state
+//! using [`Block::save_state`], and later restore it with
+//! [`Block::load_state`]. Composite blocks, such as a [`Bus`](crate::bus::Bus),
+//! recursively record the state of their components.
+//!
+//! The [`save`] and [`load`] functions wrap a block's state with a header,
+//! producing a complete save state:
+//!
+//! ```
+//! use remus::mem::Ram;
+//! use remus::{state, Address};
+//!
+//! let mut ram = Ram::<u8, 0x100>::new();
+//! ram.write(0x42usize, 0xaa);
+//! let data = state::save(&ram);
+//!
+//! // Restore into a freshly constructed block
+//! let mut ram = Ram::<u8, 0x100>::new();
+//! state::load(&mut ram, &data).unwrap();
+//! assert_eq!(ram.read(0x42usize), 0xaa);
+//! ```
+//!
+//! A save state can only be restored into a block of the same shape as the
+//! one it was recorded from. Devices shared between several mappings are
+//! recorded only once.
+//!
+//! Loads are atomic: should a save state fail to load, the block is rolled
+//! back to the state it held beforehand.
+//!
+//! # Format
+//!
+//! Save states begin with the magic bytes `RMUS`, followed by the format
+//! [version](VERSION) as a little-endian `u16`. Each block then records its
+//! state as a tagged section:
+//!
+//! | Field   | Size      | Description                          |
+//! |---------|-----------|--------------------------------------|
+//! | Tag     | `1 + len` | Length-prefixed name of the block.   |
+//! | Version | `2`       | Version of the block's section.      |
+//! | Length  | `8`       | Length of the payload, in bytes.     |
+//! | Payload | `len`     | Block state, including any children. |
+//!
+//! Integers are little-endian. Values are prefixed by their width in bytes,
+//! such that mismatched types are detected on restore.
+//!
+//! # Rewind
+//!
+//! Save states can be captured periodically into a [`Rewind`](rewind::Rewind)
+//! buffer, allowing emulation to be stepped backwards.
+
+use std::mem::size_of;
+
+use thiserror::Error;
+
+use crate::arch::Value;
+use crate::blk::Block;
+
+pub mod rewind;
+
+/// Save state format version.
+pub const VERSION: u16 = 1;
+
+/// Save state magic bytes.
+const MAGIC: &[u8; 4] = b"RMUS";
+
+/// Saves the state of a block.
+pub fn save<B>(blk: &B) -> Vec<u8>
+where
+    B: Block + ?Sized,
+{
+    save_with(blk, false)
+}
+
+/// Saves the volatile state of a block.
+///
+/// Unlike [`save`], immutable state such as ROM contents is omitted.
+fn snapshot<B>(blk: &B) -> Vec<u8>
+where
+    B: Block + ?Sized,
+{
+    save_with(blk, true)
+}
+
+/// Saves the state of a block, optionally omitting immutable state.
+fn save_with<B>(blk: &B, volatile: bool) -> Vec<u8>
+where
+    B: Block + ?Sized,
+{
+    let mut state = Writer {
+        buf: Vec::new(),
+        volatile,
+    };
+    state.raw(MAGIC);
+    state.raw(&VERSION.to_le_bytes());
+    blk.save_state(&mut state);
+    state.buf
+}
+
+/// Loads the state of a block.
+///
+/// Upon failure, the block is rolled back to its prior state, such that a
+/// failed load never partially modifies the block.
+///
+/// # Errors
+///
+/// Errors if the save state is malformed, or was recorded from a block of a
+/// different shape.
+///
+/// # Panics
+///
+/// Panics if the block cannot be rolled back, which indicates that its
+/// [`Block::save_state`] and [`Block::load_state`] are inconsistent.
+pub fn load<B>(blk: &mut B, data: &[u8]) -> Result<(), Error>
+where
+    B: Block + ?Sized,
+{
+    load_with(blk, data, false)
+}
+
+/// Loads the volatile state of a block, as recorded by [`snapshot`].
+///
+/// # Errors
+///
+/// Errors as [`load`].
+fn restore<B>(blk: &mut B, data: &[u8]) -> Result<(), Error>
+where
+    B: Block + ?Sized,
+{
+    load_with(blk, data, true)
+}
+
+/// Loads the state of a block, optionally omitting immutable state.
+fn load_with<B>(blk: &mut B, data: &[u8], volatile: bool) -> Result<(), Error>
+where
+    B: Block + ?Sized,
+{
+    let mut state = Reader::new(data, volatile);
+    if state.raw(MAGIC.len())? != MAGIC {
+        return Err(Error::Magic);
+    }
+    let found = state.u16()?;
+    if found > VERSION {
+        return Err(Error::Version {
+            found,
+            supported: VERSION,
+        });
+    }
+    // Record the current state, allowing a failed load to be rolled back
+    let prior = save_with(blk, volatile);
+    let res = blk.load_state(&mut state).and_then(|()| state.finish());
+    if res.is_err() {
+        let mut state = Reader::new(&prior[MAGIC.len() + size_of::<u16>()..], volatile);
+        blk.load_state(&mut state)
+            .expect("rolling back a failed load should succeed");
+    }
+    res
+}
+
+/// Save state writer.
+#[derive(Debug, Default)]
+pub struct Writer {
+    buf: Vec<u8>,
+    volatile: bool,
+}
+
+impl Writer {
+    /// Checks if only volatile state is being recorded.
+    ///
+    /// Blocks whose state cannot change during emulation, such as ROM, should
+    /// skip recording it when set.
+    #[must_use]
+    pub fn volatile(&self) -> bool {
+        self.volatile
+    }
+
+    /// Records a tagged section, whose payload is written by `f`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tag is longer than 255 bytes.
+    pub fn section(&mut self, tag: &str, version: u16, f: impl FnOnce(&mut Self)) {
+        let len = u8::try_from(tag.len()).expect("section tag too long");
+        self.raw(&[len]);
+        self.raw(tag.as_bytes());
+        self.raw(&version.to_le_bytes());
+        // Write the payload, then backfill its length
+        let at = self.buf.len();
+        self.raw(&[0; 8]);
+        f(self);
+        let len = (self.buf.len() - at - 8) as u64;
+        self.buf[at..at + 8].copy_from_slice(&len.to_le_bytes());
+    }
+
+    /// Records a count.
+    pub fn count(&mut self, len: usize) {
+        self.raw(&(len as u64).to_le_bytes());
+    }
+
+    /// Records a value.
+    pub fn value<V: Value>(&mut self, value: V) {
+        self.raw(&[width::<V>()]);
+        self.encode(value);
+    }
+
+    /// Records a slice of values.
+    pub fn values<V: Value>(&mut self, values: &[V]) {
+        self.raw(&[width::<V>()]);
+        self.count(values.len());
+        for &value in values {
+            self.encode(value);
+        }
+    }
+
+    /// Writes a value's bytes.
+    #[allow(clippy::cast_sign_loss)]
+    fn encode<V: Value>(&mut self, value: V) {
+        let raw = value
+            .to_u128()
+            .or_else(|| value.to_i128().map(|value| value as u128))
+            .unwrap_or_default();
+        self.raw(&raw.to_le_bytes()[..size_of::<V>()]);
+    }
+
+    /// Writes raw bytes.
+    fn raw(&mut self, data: &[u8]) {
+        self.buf.extend_from_slice(data);
+    }
+}
+
+/// Save state reader.
+#[derive(Debug)]
+pub struct Reader<'a> {
+    buf: &'a [u8],
+    volatile: bool,
+}
+
+impl<'a> Reader<'a> {
+    /// Constructs a new `Reader` over the data.
+    fn new(buf: &'a [u8], volatile: bool) -> Self {
+        Self { buf, volatile }
+    }
+
+    /// Checks if only volatile state is being restored.
+    ///
+    /// See [`Writer::volatile`].
+    #[must_use]
+    pub fn volatile(&self) -> bool {
+        self.volatile
+    }
+
+    /// Restores a tagged section, whose payload is read by `f`.
+    ///
+    /// The section's recorded version is passed to `f`, allowing older
+    /// versions to be migrated.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the section's tag does not match, its version is newer than
+    /// `version`, or `f` does not consume the entire payload.
+    pub fn section<T>(
+        &mut self,
+        tag: &str,
+        version: u16,
+        f: impl FnOnce(&mut Reader<'a>, u16) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let len = self.raw(1)?[0];
+        let found = self.raw(len.into())?;
+        if found != tag.as_bytes() {
+            return Err(Error::Section {
+                expected: tag.to_string(),
+                found: String::from_utf8_lossy(found).into_owned(),
+            });
+        }
+        let found = self.u16()?;
+        if found > version {
+            return Err(Error::Version {
+                found,
+                supported: version,
+            });
+        }
+        let len = self.count()?;
+        let mut payload = Reader::new(self.raw(len)?, self.volatile);
+        let res = f(&mut payload, found)?;
+        payload.finish()?;
+        Ok(res)
+    }
+
+    /// Restores a count.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the data is exhausted.
+    pub fn count(&mut self) -> Result<usize, Error> {
+        let mut raw = [0; 8];
+        raw.copy_from_slice(self.raw(8)?);
+        usize::try_from(u64::from_le_bytes(raw)).map_err(|_| Error::Eof)
+    }
+
+    /// Restores a value.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the data is exhausted, or the value's width does not match.
+    pub fn value<V: Value>(&mut self) -> Result<V, Error> {
+        self.width::<V>()?;
+        self.decode()
+    }
+
+    /// Restores a slice of values into the buffer.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the data is exhausted, or the values' width or count does not
+    /// match.
+    pub fn values<V: Value>(&mut self, buf: &mut [V]) -> Result<(), Error> {
+        self.width::<V>()?;
+        expect(buf.len(), self.count()?)?;
+        for value in buf {
+            *value = self.decode()?;
+        }
+        Ok(())
+    }
+
+    /// Checks the width of the upcoming values.
+    fn width<V: Value>(&mut self) -> Result<(), Error> {
+        let found = self.raw(1)?[0];
+        if found == width::<V>() {
+            Ok(())
+        } else {
+            Err(Error::Width {
+                expected: width::<V>(),
+                found,
+            })
+        }
+    }
+
+    /// Reads a value's bytes.
+    #[allow(clippy::cast_possible_wrap)]
+    fn decode<V: Value>(&mut self) -> Result<V, Error> {
+        let mut raw = [0; 16];
+        raw[..size_of::<V>()].copy_from_slice(self.raw(size_of::<V>())?);
+        let raw = u128::from_le_bytes(raw);
+        let value = if V::min_value() < V::zero() {
+            // Sign-extend signed values
+            let shift = 128 - 8 * size_of::<V>();
+            V::from((raw << shift) as i128 >> shift)
+        } else {
+            V::from(raw)
+        };
+        Ok(value.unwrap())
+    }
+
+    /// Reads a little-endian `u16`.
+    fn u16(&mut self) -> Result<u16, Error> {
+        Ok(u16::from_le_bytes(self.raw(2)?.try_into().unwrap()))
+    }
+
+    /// Reads raw bytes.
+    fn raw(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if len > self.buf.len() {
+            return Err(Error::Eof);
+        }
+        let (head, tail) = self.buf.split_at(len);
+        self.buf = tail;
+        Ok(head)
+    }
+
+    /// Checks that all data has been consumed.
+    fn finish(&self) -> Result<(), Error> {
+        if self.buf.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Trailing)
+        }
+    }
+}
+
+/// Checks that a restored length matches the expected length.
+///
+/// # Errors
+///
+/// Errors if the lengths do not match.
+pub fn expect(expected: usize, found: usize) -> Result<(), Error> {
+    if expected == found {
+        Ok(())
+    } else {
+        Err(Error::Length { expected, found })
+    }
+}
+
+/// Checks that a restored value lies below its exclusive upper bound.
+///
+/// # Errors
+///
+/// Errors if the value is out of range.
+pub fn bound(found: u64, bound: u64) -> Result<(), Error> {
+    if found < bound {
+        Ok(())
+    } else {
+        Err(Error::Range { found, bound })
+    }
+}
+
+/// Returns the width of a value, in bytes.
+#[allow(clippy::cast_possible_truncation)]
+fn width<V: Value>() -> u8 {
+    size_of::<V>() as u8
+}
+
+/// A type specifying general categories of save state error.
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum Error {
+    #[error("invalid magic bytes")]
+    Magic,
+    #[error("unsupported version: {found} (supports up to {supported})")]
+    Version {
+        /// Recorded version.
+        found: u16,
+        /// Latest supported version.
+        supported: u16,
+    },
+    #[error("mismatched section: expected {expected:?}, found {found:?}")]
+    Section {
+        /// Expected section tag.
+        expected: String,
+        /// Recorded section tag.
+        found: String,
+    },
+    #[error("mismatched value width: expected {expected}, found {found}")]
+    Width {
+        /// Expected width, in bytes.
+        expected: u8,
+        /// Recorded width, in bytes.
+        found: u8,
+    },
+    #[error("mismatched length: expected {expected}, found {found}")]
+    Length {
+        /// Expected length.
+        expected: usize,
+        /// Recorded length.
+        found: usize,
+    },
+    #[error("value out of range: {found} (expected below {bound})")]
+    Range {
+        /// Recorded value.
+        found: u64,
+        /// Exclusive upper bound.
+        bound: u64,
+    },
+    #[error("unexpected end of data")]
+    Eof,
+    #[error("unexpected trailing data")]
+    Trailing,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::adapt::{Bank, Mask};
+    use crate::bus::{Bus, Mux};
+    use crate::dev::Device;
+    use crate::mem::{Ram, Rom};
+    use crate::reg::Register;
+    use crate::wired::Wired;
+    use crate::{Address, Cell, Machine};
+
+    fn setup() -> Bus<u16, u8> {
+        let mut bank = Bank::new();
+        bank.add(Ram::<u8, 0x10>::new().to_dynamic());
+        bank.add(Ram::<u8, 0x10>::new().to_dynamic());
+        let mut bus = Bus::new();
+        bus.map(0x0000..=0x00ff, Rom::<u8, 0x100>::new().to_dynamic());
+        bus.map(0x0100..=0x010f, bank.to_dynamic());
+        bus.map(0x0110..=0x0110, Register::<u8>::new().to_dynamic());
+        bus
+    }
+
+    #[test]
+    fn save_load_works() {
+        let mut bus = setup();
+        bus.poke(0x0042, 0xaa);
+        bus.write(0x0101, 0xbb);
+        bus.write(0x0110, 0xcc);
+        let data = save(&bus);
+        // Restore into a fresh bus
+        let mut bus = setup();
+        load(&mut bus, &data).unwrap();
+        assert_eq!(bus.read(0x0042), 0xaa);
+        assert_eq!(bus.read(0x0101), 0xbb);
+        assert_eq!(bus.read(0x0110), 0xcc);
+        assert_eq!(save(&bus), data);
+    }
+
+    #[test]
+    fn save_load_bank_works() {
+        let setup = || {
+            let mut bank = Bank::<u16, u8>::new();
+            bank.add(Ram::<u8, 0x10>::new().to_dynamic());
+            bank.add(Ram::<u8, 0x10>::new().to_dynamic());
+            bank
+        };
+        let mut bank = setup();
+        bank.set(1);
+        bank.write(0x0001, 0xaa);
+        let data = save(&bank);
+        let mut bank = setup();
+        load(&mut bank, &data).unwrap();
+        assert_eq!(bank.get(), 1);
+        assert_eq!(bank.read(0x0001), 0xaa);
+        bank.set(0);
+        assert_eq!(bank.read(0x0001), 0x00);
+    }
+
+    #[test]
+    fn save_load_mask_works() {
+        let mut mask = Mask::<Bus<u16, u8>, u16, u8>::new();
+        for value in [0xaa, 0xbb] {
+            let mut bus = Bus::new();
+            bus.map(0x00..=0x0f, Ram::from(&[value; 0x10]).to_dynamic());
+            mask.push(bus);
+        }
+        mask.write(0x04, 0xcc);
+        let data = save(&mask);
+        let mut mask = Mask::<Bus<u16, u8>, u16, u8>::new();
+        for _ in 0..2 {
+            let mut bus = Bus::new();
+            bus.map(0x00..=0x0f, Ram::<u8, 0x10>::new().to_dynamic());
+            mask.push(bus);
+        }
+        load(&mut mask, &data).unwrap();
+        assert_eq!(mask.read(0x04), 0xcc);
+        assert_eq!(mask.layer(1).unwrap().read(0x04), 0xbb);
+    }
+
+    #[test]
+    fn save_load_wired_works() {
+        let mut wired: Wired<Ram<u8, 0x10>, usize, u8> = Wired::default();
+        wired.write(0x1, 0xaa);
+        wired.read(0x1);
+        let data = save(&wired);
+        let mut wired: Wired<Ram<u8, 0x10>, usize, u8> = Wired::default();
+        load(&mut wired, &data).unwrap();
+        // Wires remain acquired until the next cycle
+        assert_eq!(wired.read(0x2), 0xaa);
+        wired.cycle();
+        assert_eq!(wired.read(0x2), 0x00);
+    }
+
+    #[test]
+    fn save_load_signed_works() {
+        let mut reg = Register::<i16>::from(-2);
+        let data = save(&reg);
+        reg.store(0);
+        load(&mut reg, &data).unwrap();
+        assert_eq!(reg.load(), -2);
+    }
+
+    #[test]
+    fn load_magic_fails() {
+        let mut ram = Ram::<u8, 0x10>::new();
+        let mut data = save(&ram);
+        data[0] = b'X';
+        assert_eq!(load(&mut ram, &data), Err(Error::Magic));
+    }
+
+    #[test]
+    fn load_version_fails() {
+        let mut ram = Ram::<u8, 0x10>::new();
+        let mut data = save(&ram);
+        data[4..6].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        assert_eq!(
+            load(&mut ram, &data),
+            Err(Error::Version {
+                found: VERSION + 1,
+                supported: VERSION,
+            })
+        );
+    }
+
+    #[test]
+    fn load_mismatch_fails() {
+        let data = save(&Ram::<u8, 0x10>::new());
+        assert_eq!(
+            load(&mut Ram::<u8, 0x20>::new(), &data),
+            Err(Error::Length {
+                expected: 0x20,
+                found: 0x10,
+            })
+        );
+        assert_eq!(
+            load(&mut Ram::<u16, 0x10>::new(), &data),
+            Err(Error::Width {
+                expected: 2,
+                found: 1,
+            })
+        );
+        assert_eq!(
+            load(&mut Rom::<u8, 0x10>::new(), &data),
+            Err(Error::Section {
+                expected: String::from("rom"),
+                found: String::from("ram"),
+            })
+        );
+        assert_eq!(
+            load(&mut Ram::<u8, 0x10>::new(), &data[..data.len() - 1]),
+            Err(Error::Eof)
+        );
+    }
+
+    /// Locates the first value recorded within a section.
+    fn find(data: &[u8], tag: &str) -> usize {
+        let mut head = vec![u8::try_from(tag.len()).unwrap()];
+        head.extend_from_slice(tag.as_bytes());
+        let at = data.windows(head.len()).position(|it| it == head).unwrap();
+        // Skip the tag, version, length and value width
+        at + head.len() + 2 + 8 + 1
+    }
+
+    #[test]
+    fn load_range_fails() {
+        let mut bus = setup();
+        bus.write(0x0101, 0xaa);
+        let mut data = save(&bus);
+        let at = find(&data, "bank");
+        data[at] = 2;
+        assert_eq!(
+            load(&mut setup(), &data),
+            Err(Error::Range { found: 2, bound: 2 })
+        );
+        let wired: Wired<Ram<u8, 0x10>, usize, u8> = Wired::default();
+        let mut data = save(&wired);
+        let at = find(&data, "wire");
+        data[at] = 2;
+        assert_eq!(
+            load(&mut Wired::<Ram<u8, 0x10>, usize, u8>::default(), &data),
+            Err(Error::Range { found: 2, bound: 2 })
+        );
+    }
+
+    #[test]
+    fn load_rollback_works() {
+        let mut bus = setup();
+        bus.write(0x0101, 0xaa);
+        bus.write(0x0110, 0xbb);
+        let mut data = save(&bus);
+        let at = find(&data, "bank");
+        data[at] = 2;
+        bus.write(0x0101, 0xcc);
+        bus.write(0x0110, 0xdd);
+        // Devices restored before the failure are rolled back
+        assert!(load(&mut bus, &data).is_err());
+        assert_eq!(bus.read(0x0101), 0xcc);
+        assert_eq!(bus.read(0x0110), 0xdd);
+    }
+
+    #[test]
+    fn save_load_shared_works() {
+        let ram = Ram::<u8, 0x10>::new().to_dynamic();
+        let mut once = Bus::<u16, u8>::new();
+        once.map(0x00..=0x0f, ram.clone());
+        let mut twice = Bus::<u16, u8>::new();
+        twice.map(0x00..=0x0f, ram.clone());
+        twice.map(0x10..=0x1f, ram);
+        // Shared devices are recorded once
+        twice.write(0x04, 0xaa);
+        once.write(0x04, 0xaa);
+        let data = save(&twice);
+        assert_eq!(data, save(&once));
+        twice.write(0x14, 0xbb);
+        load(&mut twice, &data).unwrap();
+        assert_eq!(twice.read(0x14), 0xaa);
+    }
+}
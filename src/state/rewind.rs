@@ -0,0 +1,317 @@
+//! Rewind buffer.
+//!
+//! # Usage
+//!
+//! A [`Rewind`] buffer periodically captures snapshots of a [`Block`], such as
+//! a [`Board`](crate::Board), retaining a bounded history which can later be
+//! restored:
+//!
+//! ```
+//! use remus::mem::Ram;
+//! use remus::state::rewind::Rewind;
+//! use remus::Address;
+//!
+//! let mut ram = Ram::<u8, 0x100>::new();
+//! // Capture a snapshot every 4 cycles, retaining up to 60 snapshots
+//! let mut rewind = Rewind::new(60, 4);
+//! for cycle in 0..16usize {
+//!     ram.write(cycle, 0xaa);
+//!     rewind.cycle(&ram);
+//! }
+//! assert_eq!(rewind.len(), 4);
+//!
+//! // Restore the state from two snapshots ago
+//! rewind.rewind(&mut ram, 2).unwrap();
+//! assert_eq!(ram.read(0x07usize), 0xaa);
+//! assert_eq!(ram.read(0x08usize), 0x00);
+//! ```
+//!
+//! Only the most recent snapshot is stored in full. Older snapshots are stored
+//! as deltas against their successor, such that only the regions which changed
+//! between snapshots consume memory. Note that deltas are found by comparing
+//! consecutive snapshots in 64-byte chunks, rather than from any
+//! [dirty tracking](crate::mem::Ram#dirty-tracking), so each capture still
+//! serializes and compares the entire block.
+//!
+//! Snapshots record only volatile state; immutable contents such as those of
+//! a [`Rom`](crate::mem::Rom) are neither captured nor restored. As such,
+//! [pokes](crate::dev::Device::poke) into ROM are not rewound.
+
+use std::collections::VecDeque;
+
+use thiserror::Error;
+
+use crate::blk::Block;
+use crate::state;
+
+/// Granularity of snapshot deltas, in bytes.
+const CHUNK: usize = 64;
+
+/// Snapshot rewind buffer.
+#[derive(Debug)]
+pub struct Rewind {
+    cap: usize,
+    period: usize,
+    count: usize,
+    head: Option<Vec<u8>>,
+    hist: VecDeque<Delta>,
+}
+
+impl Rewind {
+    /// Constructs a new `Rewind` buffer, retaining up to `cap` snapshots each
+    /// captured every `period` cycles.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either `cap` or `period` is zero.
+    #[must_use]
+    pub fn new(cap: usize, period: usize) -> Self {
+        assert!(cap > 0, "rewind capacity must be non-zero");
+        assert!(period > 0, "rewind period must be non-zero");
+        Self {
+            cap,
+            period,
+            count: 0,
+            head: None,
+            hist: VecDeque::new(),
+        }
+    }
+
+    /// Returns the number of retained snapshots.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.head.as_ref().map_or(0, |_| 1 + self.hist.len())
+    }
+
+    /// Checks if no snapshots are retained.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    /// Returns the total size of the retained snapshots, in bytes.
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.head.as_ref().map_or(0, Vec::len) + self.hist.iter().map(Delta::size).sum::<usize>()
+    }
+
+    /// Discards all retained snapshots.
+    pub fn clear(&mut self) {
+        self.count = 0;
+        self.head = None;
+        self.hist.clear();
+    }
+
+    /// Advances by a cycle, capturing a snapshot once every period.
+    ///
+    /// This is intended to be called alongside [`Machine::cycle`].
+    ///
+    /// [`Machine::cycle`]: crate::Machine::cycle
+    pub fn cycle<B>(&mut self, blk: &B)
+    where
+        B: Block + ?Sized,
+    {
+        self.count += 1;
+        if self.count >= self.period {
+            self.count = 0;
+            self.capture(blk);
+        }
+    }
+
+    /// Captures a snapshot.
+    ///
+    /// If the buffer is full, the oldest snapshot is discarded.
+    pub fn capture<B>(&mut self, blk: &B)
+    where
+        B: Block + ?Sized,
+    {
+        let next = state::snapshot(blk);
+        if let Some(prev) = self.head.take() {
+            // Record how to recover the previous snapshot from its successor
+            self.hist.push_back(Delta::new(&next, &prev));
+        }
+        self.head = Some(next);
+        if self.len() > self.cap {
+            self.hist.pop_front();
+        }
+    }
+
+    /// Restores the snapshot captured `n` snapshots before the most recent,
+    /// discarding all snapshots newer than it.
+    ///
+    /// # Errors
+    ///
+    /// Errors if fewer than `n + 1` snapshots are retained, or the snapshot
+    /// could not be loaded. Upon error, both the buffer and the block are left
+    /// unchanged.
+    pub fn rewind<B>(&mut self, blk: &mut B, n: usize) -> Result<(), Error>
+    where
+        B: Block + ?Sized,
+    {
+        let Some(head) = &self.head else {
+            return Err(Error::Depth { n, len: 0 });
+        };
+        if n >= self.len() {
+            return Err(Error::Depth { n, len: self.len() });
+        }
+        // Reconstruct the snapshot by walking back through the deltas
+        let mut snap = head.clone();
+        for delta in self.hist.iter().rev().take(n) {
+            delta.apply(&mut snap);
+        }
+        state::restore(blk, &snap)?;
+        // Discard newer snapshots
+        self.hist.truncate(self.hist.len() - n);
+        self.head = Some(snap);
+        self.count = 0;
+        Ok(())
+    }
+}
+
+/// Snapshot delta.
+///
+/// Records the chunks of a snapshot which differ from its successor.
+#[derive(Debug)]
+struct Delta {
+    len: usize,
+    diff: Vec<(usize, Vec<u8>)>,
+}
+
+impl Delta {
+    /// Constructs the `Delta` which transforms `from` into `to`.
+    fn new(from: &[u8], to: &[u8]) -> Self {
+        let mut diff: Vec<(usize, Vec<u8>)> = Vec::new();
+        for (idx, chunk) in to.chunks(CHUNK).enumerate() {
+            let off = idx * CHUNK;
+            if from.get(off..off + chunk.len()) == Some(chunk) {
+                continue;
+            }
+            match diff.last_mut() {
+                // Coalesce adjacent chunks
+                Some((at, data)) if *at + data.len() == off => data.extend_from_slice(chunk),
+                _ => diff.push((off, chunk.to_vec())),
+            }
+        }
+        Self {
+            len: to.len(),
+            diff,
+        }
+    }
+
+    /// Applies the delta to a snapshot.
+    fn apply(&self, snap: &mut Vec<u8>) {
+        snap.resize(self.len, 0);
+        for (off, data) in &self.diff {
+            snap[*off..*off + data.len()].copy_from_slice(data);
+        }
+    }
+
+    /// Returns the size of the delta's data, in bytes.
+    fn size(&self) -> usize {
+        self.diff.iter().map(|(_, data)| data.len()).sum()
+    }
+}
+
+/// A type specifying general categories of [`Rewind`] error.
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum Error {
+    #[error("cannot rewind {n} snapshots (only {len} retained)")]
+    Depth {
+        /// Requested number of snapshots.
+        n: usize,
+        /// Number of retained snapshots.
+        len: usize,
+    },
+    #[error(transparent)]
+    State(#[from] state::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bus::{Bus, Mux};
+    use crate::dev::Device;
+    use crate::mem::{Ram, Rom};
+    use crate::Address;
+
+    fn setup() -> Bus<u16, u8> {
+        let mut bus = Bus::new();
+        bus.map(0x0000..=0x3fff, Ram::<u8, 0x4000>::new().to_dynamic());
+        bus
+    }
+
+    #[test]
+    fn capture_works() {
+        let mut bus = setup();
+        let mut rewind = Rewind::new(3, 1);
+        assert!(rewind.is_empty());
+        for frame in 0..5 {
+            bus.write(0x0000, frame);
+            rewind.capture(&bus);
+        }
+        // Oldest snapshots are discarded
+        assert_eq!(rewind.len(), 3);
+        rewind.rewind(&mut bus, 2).unwrap();
+        assert_eq!(bus.read(0x0000), 2);
+        assert_eq!(rewind.len(), 1);
+    }
+
+    #[test]
+    fn cycle_works() {
+        let mut bus = setup();
+        let mut rewind = Rewind::new(8, 10);
+        for cycle in 0..100u16 {
+            bus.write(cycle, 0xaa);
+            rewind.cycle(&bus);
+        }
+        assert_eq!(rewind.len(), 8);
+        rewind.rewind(&mut bus, 1).unwrap();
+        assert_eq!(bus.read(0x0059), 0xaa);
+        assert_eq!(bus.read(0x005a), 0x00);
+    }
+
+    #[test]
+    fn delta_works() {
+        let mut bus = setup();
+        let mut rewind = Rewind::new(60, 1);
+        rewind.capture(&bus);
+        let full = rewind.size();
+        for frame in 0..59 {
+            bus.write(0x1000, frame);
+            rewind.capture(&bus);
+        }
+        // Only changed chunks are retained
+        assert!(rewind.size() < full + 59 * 2 * CHUNK);
+        rewind.rewind(&mut bus, 59).unwrap();
+        assert_eq!(bus.read(0x1000), 0);
+    }
+
+    #[test]
+    fn capture_rom_works() {
+        let mut bus = setup();
+        bus.map(0x4000..=0x7fff, Rom::<u8, 0x4000>::new().to_dynamic());
+        let mut rewind = Rewind::new(2, 1);
+        rewind.capture(&bus);
+        // ROM contents are not captured
+        assert!(rewind.size() < 0x4000 + 0x100);
+        bus.poke(0x4000, 0xaa);
+        rewind.rewind(&mut bus, 0).unwrap();
+        assert_eq!(bus.read(0x4000), 0xaa);
+    }
+
+    #[test]
+    fn rewind_depth_fails() {
+        let mut bus = setup();
+        let mut rewind = Rewind::new(4, 1);
+        assert_eq!(
+            rewind.rewind(&mut bus, 0),
+            Err(Error::Depth { n: 0, len: 0 })
+        );
+        rewind.capture(&bus);
+        assert_eq!(
+            rewind.rewind(&mut bus, 1),
+            Err(Error::Depth { n: 1, len: 1 })
+        );
+        assert_eq!(rewind.rewind(&mut bus, 0), Ok(()));
+    }
+}
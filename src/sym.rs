@@ -0,0 +1,408 @@
+//! Symbol tables.
+//!
+//! # Usage
+//!
+//! A [`Table`] maps addresses to labels, allowing tracing and debugging tools
+//! to present `label+offset` in place of raw addresses:
+//!
+//! ```
+//! use remus::sym::Table;
+//!
+//! let table = Table::sym("00:0150 Main\n01:4000 Banked\n").unwrap();
+//! let label = table.resolve(Some(0), 0x0153u16).unwrap();
+//! assert_eq!(label.to_string(), "Main+0x3");
+//! assert_eq!(table.find("Banked"), Some((Some(1), 0x4000)));
+//! ```
+//!
+//! The following formats are supported:
+//!
+//! | Format                  | Constructor                     |
+//! |-------------------------|---------------------------------|
+//! | `bank:addr label` files | [`Table::sym`]                  |
+//! | `name = addr` maps      | [`Table::map`]                  |
+//! | ELF symbols             | `Table::from(image.symbols())`  |
+//!
+//! See [`Image::symbols`](crate::load::Image::symbols) for loading symbols from
+//! an ELF executable.
+//!
+//! # Banking
+//!
+//! Symbols within [`Bank`](crate::bus::adapt::Bank)-switched regions can be
+//! qualified by their bank, such that the same address resolves to a different
+//! label depending on the currently selected bank. Unqualified symbols resolve
+//! regardless of the queried bank.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+
+use thiserror::Error;
+
+use crate::arch::Value;
+use crate::load::Symbol;
+
+/// Symbol table.
+#[derive(Clone, Debug, Default)]
+pub struct Table {
+    addrs: BTreeMap<Option<usize>, BTreeMap<u64, Vec<Entry>>>,
+    names: HashMap<String, (Option<usize>, u64)>,
+}
+
+/// Symbol table entry.
+#[derive(Clone, Debug)]
+struct Entry {
+    name: String,
+    size: Option<u64>,
+}
+
+impl Table {
+    /// Constructs a new, empty `Table`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses a `Table` from a `bank:addr label` symbol file.
+    ///
+    /// Banks and addresses are hexadecimal. Comments begin with `;` or `#`.
+    ///
+    /// # Errors
+    ///
+    /// Errors if any line is malformed.
+    pub fn sym(src: &str) -> Result<Self, Error> {
+        let mut this = Self::new();
+        for (line, text) in lines(src) {
+            let malformed = |what| Error::Format { line, what };
+            let (loc, name) = text
+                .split_once(char::is_whitespace)
+                .ok_or(malformed("missing label"))?;
+            let (bank, addr) = loc.split_once(':').ok_or(malformed("missing bank"))?;
+            let bank = usize::from_str_radix(bank, 16).map_err(|_| malformed("invalid bank"))?;
+            let addr = u64::from_str_radix(addr, 16).map_err(|_| malformed("invalid address"))?;
+            this.insert(Some(bank), addr, name.trim());
+        }
+        Ok(this)
+    }
+
+    /// Parses a `Table` from a `name = addr` map.
+    ///
+    /// Addresses are hexadecimal if prefixed by `0x` or `$`, and decimal
+    /// otherwise. Comments begin with `;` or `#`, allowing for linker-style
+    /// `name = addr;` assignments.
+    ///
+    /// # Errors
+    ///
+    /// Errors if any line is malformed.
+    pub fn map(src: &str) -> Result<Self, Error> {
+        let mut this = Self::new();
+        for (line, text) in lines(src) {
+            let malformed = |what| Error::Format { line, what };
+            let (name, addr) = text.split_once('=').ok_or(malformed("missing `=`"))?;
+            let addr = addr.trim();
+            let addr = match addr
+                .strip_prefix("0x")
+                .or_else(|| addr.strip_prefix("0X"))
+                .or_else(|| addr.strip_prefix('$'))
+            {
+                Some(hex) => u64::from_str_radix(hex, 16),
+                None => addr.parse(),
+            }
+            .map_err(|_| malformed("invalid address"))?;
+            let name = name.trim();
+            if name.is_empty() {
+                return Err(malformed("missing label"));
+            }
+            this.insert(None, addr, name);
+        }
+        Ok(this)
+    }
+
+    /// Inserts a symbol into the table.
+    ///
+    /// Where multiple symbols share a location, the first inserted is
+    /// preferred when resolving addresses. All can still be
+    /// [found](Table::find).
+    pub fn insert(&mut self, bank: Option<usize>, addr: u64, name: impl Into<String>) {
+        self.insert_sized(bank, addr, name.into(), None);
+    }
+
+    /// Inserts a symbol of a known size into the table.
+    fn insert_sized(&mut self, bank: Option<usize>, addr: u64, name: String, size: Option<u64>) {
+        self.names.entry(name.clone()).or_insert((bank, addr));
+        self.addrs
+            .entry(bank)
+            .or_default()
+            .entry(addr)
+            .or_default()
+            .push(Entry { name, size });
+    }
+
+    /// Returns the number of symbols in the table.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.addrs
+            .values()
+            .flat_map(BTreeMap::values)
+            .map(Vec::len)
+            .sum()
+    }
+
+    /// Checks if the table contains no symbols.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Finds the location of a symbol by name, as its bank and address.
+    #[must_use]
+    pub fn find(&self, name: &str) -> Option<(Option<usize>, u64)> {
+        self.names.get(name).copied()
+    }
+
+    /// Resolves an address to the nearest preceding symbol.
+    ///
+    /// Symbols qualified by `bank` are considered, as are unqualified symbols.
+    /// Where both are equally near, the qualified symbol is preferred.
+    /// Where a symbol's size is known, addresses beyond it do not resolve to
+    /// that symbol, and instead resolve to the nearest symbol covering them.
+    #[must_use]
+    pub fn resolve<Idx>(&self, bank: Option<usize>, index: Idx) -> Option<Label<'_>>
+    where
+        Idx: Value,
+    {
+        let addr = index.to_u64()?;
+        let near = |bank| {
+            // Skip past sized symbols which end before the address
+            self.addrs.get(&bank).and_then(|syms| {
+                syms.range(..=addr).rev().find_map(|(&base, entries)| {
+                    entries
+                        .iter()
+                        .find(|entry| entry.covers(addr - base))
+                        .map(|entry| (base, entry))
+                })
+            })
+        };
+        // Prefer the nearest symbol, breaking ties in favour of qualified ones
+        let ((base, entry), _) = [
+            near(bank).map(|near| (near, bank.is_some())),
+            bank.and(near(None)).map(|near| (near, false)),
+        ]
+        .into_iter()
+        .flatten()
+        .max_by_key(|&((base, _), qual)| (base, qual))?;
+        Some(Label {
+            name: &entry.name,
+            offset: addr - base,
+        })
+    }
+}
+
+impl Entry {
+    /// Checks if the symbol covers an offset from its address.
+    ///
+    /// Symbols of unknown (or zero) size cover all offsets.
+    fn covers(&self, offset: u64) -> bool {
+        self.size.map_or(true, |size| size == 0 || offset < size)
+    }
+}
+
+impl From<&[Symbol]> for Table {
+    fn from(syms: &[Symbol]) -> Self {
+        let mut this = Self::new();
+        for sym in syms {
+            this.insert_sized(None, sym.addr, sym.name.clone(), Some(sym.size));
+        }
+        this
+    }
+}
+
+/// Resolved symbol label.
+///
+/// Displayed as `label+offset`, omitting a zero offset.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Label<'a> {
+    /// Symbol name.
+    pub name: &'a str,
+    /// Offset from the symbol's address.
+    pub offset: u64,
+}
+
+impl Display for Label<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        if self.offset != 0 {
+            write!(f, "+{:#x}", self.offset)?;
+        }
+        Ok(())
+    }
+}
+
+/// Iterates over the non-empty lines of a symbol file, stripping comments.
+fn lines(src: &str) -> impl Iterator<Item = (usize, &str)> {
+    (1..).zip(src.lines()).filter_map(|(line, text)| {
+        let text = text.split([';', '#']).next().unwrap_or_default().trim();
+        (!text.is_empty()).then_some((line, text))
+    })
+}
+
+/// A type specifying general categories of [`Table`] error.
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum Error {
+    #[error("malformed symbol on line {line}: {what}")]
+    Format {
+        /// Source line of the symbol.
+        line: usize,
+        /// Description of the problem.
+        what: &'static str,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sym_works() {
+        let table = Table::sym(
+            "; comment\n\
+             00:0100 Entry\n\
+             00:0150 Main ; trailing\n\
+             01:4000 BankOne\n\
+             02:4000 BankTwo\n",
+        )
+        .unwrap();
+        assert_eq!(table.len(), 4);
+        assert_eq!(table.find("Main"), Some((Some(0), 0x0150)));
+        assert_eq!(
+            table.resolve(Some(0), 0x0100u16).unwrap().to_string(),
+            "Entry"
+        );
+        assert_eq!(
+            table.resolve(Some(0), 0x014fu16).unwrap().to_string(),
+            "Entry+0x4f"
+        );
+        // Banked addresses resolve by bank
+        assert_eq!(table.resolve(Some(1), 0x4010u16).unwrap().name, "BankOne");
+        assert_eq!(table.resolve(Some(2), 0x4010u16).unwrap().name, "BankTwo");
+        assert_eq!(table.resolve(Some(3), 0x4010u16), None);
+    }
+
+    #[test]
+    fn map_works() {
+        let table = Table::map("_start = 0x8000;\nstack = $c000\n# comment\nirq = 64\n").unwrap();
+        assert_eq!(table.find("_start"), Some((None, 0x8000)));
+        assert_eq!(table.find("stack"), Some((None, 0xc000)));
+        assert_eq!(table.find("irq"), Some((None, 64)));
+        // Unqualified symbols resolve in any bank
+        assert_eq!(
+            table.resolve(Some(7), 0x8002u16).unwrap().to_string(),
+            "_start+0x2"
+        );
+        assert_eq!(table.resolve(None, 0x0010u16), None);
+    }
+
+    #[test]
+    fn elf_works() {
+        let syms = [
+            Symbol {
+                name: "func".to_string(),
+                addr: 0x1000,
+                size: 0x10,
+            },
+            Symbol {
+                name: "label".to_string(),
+                addr: 0x2000,
+                size: 0,
+            },
+        ];
+        let table = Table::from(&syms[..]);
+        assert_eq!(
+            table.resolve(None, 0x100fu32).unwrap().to_string(),
+            "func+0xf"
+        );
+        // Addresses beyond a sized symbol do not resolve to it
+        assert_eq!(table.resolve(None, 0x1010u32), None);
+        assert_eq!(
+            table.resolve(None, 0x2100u32).unwrap().to_string(),
+            "label+0x100"
+        );
+    }
+
+    #[test]
+    fn resolve_nested_works() {
+        let syms = [
+            Symbol {
+                name: "outer".to_string(),
+                addr: 0x1000,
+                size: 0x100,
+            },
+            Symbol {
+                name: "inner".to_string(),
+                addr: 0x1010,
+                size: 0x10,
+            },
+            Symbol {
+                name: "alias".to_string(),
+                addr: 0x1010,
+                size: 0x20,
+            },
+        ];
+        let table = Table::from(&syms[..]);
+        // Symbols sharing an address are all counted
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.find("alias"), Some((None, 0x1010)));
+        assert_eq!(
+            table.resolve(None, 0x1014u32).unwrap().to_string(),
+            "inner+0x4"
+        );
+        // Resolution continues past symbols which are too short
+        assert_eq!(
+            table.resolve(None, 0x1024u32).unwrap().to_string(),
+            "alias+0x14"
+        );
+        assert_eq!(
+            table.resolve(None, 0x1080u32).unwrap().to_string(),
+            "outer+0x80"
+        );
+        assert_eq!(table.resolve(None, 0x1100u32), None);
+    }
+
+    #[test]
+    fn resolve_tie_works() {
+        let mut table = Table::new();
+        table.insert(None, 0x4000, "Global");
+        table.insert(Some(1), 0x4000, "BankOne");
+        table.insert(Some(2), 0x4000, "BankTwo");
+        // Qualified symbols are preferred over equally near unqualified ones
+        assert_eq!(table.resolve(Some(1), 0x4010u16).unwrap().name, "BankOne");
+        assert_eq!(table.resolve(Some(2), 0x4010u16).unwrap().name, "BankTwo");
+        assert_eq!(table.resolve(Some(3), 0x4010u16).unwrap().name, "Global");
+        // ... but not over nearer ones
+        table.insert(None, 0x4008, "Nearer");
+        assert_eq!(table.resolve(Some(1), 0x4010u16).unwrap().name, "Nearer");
+    }
+
+    #[test]
+    fn parse_fails() {
+        assert_eq!(
+            Table::sym("00:0100 Entry\n00:0150").unwrap_err(),
+            Error::Format {
+                line: 2,
+                what: "missing label",
+            }
+        );
+        assert_eq!(
+            Table::sym("zz:0100 Entry").unwrap_err(),
+            Error::Format {
+                line: 1,
+                what: "invalid bank",
+            }
+        );
+        assert_eq!(
+            Table::map("x = 0xzz").unwrap_err(),
+            Error::Format {
+                line: 1,
+                what: "invalid address",
+            }
+        );
+    }
+}
@@ -1,8 +1,11 @@
 use std::cell::RefCell;
+use std::ops::RangeInclusive;
 
-use crate::arch::{TryAddress, Value};
-use crate::bus::{Mux, Range};
-use crate::dev::{Device, Dynamic};
+use crate::arch::{Access, Context, TryAddress, Value};
+use crate::bus::{Handle, Mux, Range, Region};
+use crate::dev::{self, Device, Dynamic};
+#[cfg(feature = "state")]
+use crate::state;
 use crate::{Address, Block, Machine};
 
 /// Buffered device.
@@ -80,6 +83,23 @@ where
     Idx: Value,
     V: Value,
 {
+    #[cfg(feature = "state")]
+    fn save_state(&self, state: &mut state::Writer) {
+        state.section("wired", 1, |state| {
+            self.index.borrow().save_state(state);
+            self.value.borrow().save_state(state);
+            self.inner.save_state(state);
+        });
+    }
+
+    #[cfg(feature = "state")]
+    fn load_state(&mut self, state: &mut state::Reader) -> Result<(), state::Error> {
+        state.section("wired", 1, |state, _| {
+            self.index.get_mut().load_state(state)?;
+            self.value.get_mut().load_state(state)?;
+            self.inner.load_state(state)
+        })
+    }
 }
 
 impl<T, Idx, V> Device<Idx, V> for Wired<T, Idx, V>
@@ -88,6 +108,74 @@ where
     Idx: Value,
     V: Value,
 {
+    fn try_read_dyn(&self, index: Idx) -> Result<V, dev::Error> {
+        let index = self.index.borrow().get().unwrap_or(index);
+        let value = self
+            .value
+            .borrow()
+            .get()
+            .map_or_else(|| self.inner.try_read_dyn(index), |value| Ok(value))?;
+        self.index.borrow_mut().acquire(index);
+        self.value.borrow_mut().acquire(value);
+        Ok(value)
+    }
+
+    fn try_write_dyn(&mut self, index: Idx, value: V) -> Result<(), dev::Error> {
+        self.inner.try_write_dyn(
+            self.index.borrow().get().unwrap_or(index),
+            self.value.borrow().get().unwrap_or(value),
+        )
+    }
+
+    fn try_read_ctx(&self, index: Idx, ctx: Context) -> Result<V, dev::Error> {
+        // Debug reads must not acquire the wires
+        if ctx.access == Access::Debug {
+            return Ok(self.peek(index));
+        }
+        let index = self.index.borrow().get().unwrap_or(index);
+        let value = self
+            .value
+            .borrow()
+            .get()
+            .map_or_else(|| self.inner.try_read_ctx(index, ctx), |value| Ok(value))?;
+        self.index.borrow_mut().acquire(index);
+        self.value.borrow_mut().acquire(value);
+        Ok(value)
+    }
+
+    fn try_write_ctx(&mut self, index: Idx, value: V, ctx: Context) -> Result<(), dev::Error> {
+        if ctx.access == Access::Debug {
+            self.poke(index, value);
+            return Ok(());
+        }
+        self.inner.try_write_ctx(
+            self.index.borrow().get().unwrap_or(index),
+            self.value.borrow().get().unwrap_or(value),
+            ctx,
+        )
+    }
+
+    fn dirty(&self) -> Vec<RangeInclusive<usize>> {
+        self.inner.dirty()
+    }
+
+    fn clean(&mut self) {
+        self.inner.clean();
+    }
+
+    /// Peeks the value that would be read, without acquiring the wires.
+    fn peek(&self, index: Idx) -> V {
+        let index = self.index.borrow().get().unwrap_or(index);
+        self.value
+            .borrow()
+            .get()
+            .unwrap_or_else(|| self.inner.peek(index))
+    }
+
+    /// Pokes the inner device directly, bypassing the wires.
+    fn poke(&mut self, index: Idx, value: V) {
+        self.inner.poke(index, value);
+    }
 }
 
 impl<T, Idx, V> Machine for Wired<T, Idx, V>
@@ -116,13 +204,33 @@ where
         self.inner.get(index)
     }
 
-    fn map(&mut self, range: Range<Idx>, dev: Dynamic<Idx, V>) {
-        self.inner.map(range, dev);
+    fn map(&mut self, range: Range<Idx>, dev: Dynamic<Idx, V>) -> Handle {
+        self.inner.map(range, dev)
     }
 
     fn unmap(&mut self, dev: &Dynamic<Idx, V>) -> Option<Dynamic<Idx, V>> {
         self.inner.unmap(dev)
     }
+
+    fn unmap_handle(&mut self, handle: Handle) -> Option<Dynamic<Idx, V>> {
+        self.inner.unmap_handle(handle)
+    }
+
+    fn unmap_range(&mut self, range: Range<Idx>) -> Vec<Dynamic<Idx, V>> {
+        self.inner.unmap_range(range)
+    }
+
+    fn remap(&mut self, handle: Handle, range: Range<Idx>) -> bool {
+        self.inner.remap(handle, range)
+    }
+
+    fn label(&mut self, handle: Handle, label: Option<String>) -> bool {
+        self.inner.label(handle, label)
+    }
+
+    fn regions(&self) -> Vec<Region<Idx, V>> {
+        self.inner.regions()
+    }
 }
 
 /// Tri-state buffered wire.
@@ -169,4 +277,58 @@ where
             Wire::Active(value) => Some(*value),
         }
     }
+
+    /// Saves the state of the wire.
+    #[cfg(feature = "state")]
+    pub fn save_state(&self, state: &mut state::Writer) {
+        state.section("wire", 1, |state| match self {
+            Wire::Impedant => state.value(0u8),
+            Wire::Active(value) => {
+                state.value(1u8);
+                state.value(*value);
+            }
+        });
+    }
+
+    /// Loads the state of the wire.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the recorded state is malformed.
+    #[cfg(feature = "state")]
+    pub fn load_state(&mut self, state: &mut state::Reader) -> Result<(), state::Error> {
+        state.section("wire", 1, |state, _| {
+            *self = match state.value::<u8>()? {
+                0 => Wire::Impedant,
+                1 => Wire::Active(state.value()?),
+                tag => {
+                    return Err(state::Error::Range {
+                        found: tag.into(),
+                        bound: 2,
+                    })
+                }
+            };
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mem::Ram;
+
+    #[test]
+    fn probe_works() {
+        let mut wired: Wired<Ram<u8, 0x10>, usize, u8> = Wired::default();
+        wired.poke(0x1, 0xaa);
+        wired.poke(0x2, 0xbb);
+        // Peeks do not acquire the wires
+        assert_eq!(wired.peek(0x1), 0xaa);
+        assert_eq!(wired.read(0x2), 0xbb);
+        // Peeks observe acquired wires
+        assert_eq!(wired.peek(0x1), 0xbb);
+        wired.cycle();
+        assert_eq!(wired.peek(0x1), 0xaa);
+    }
 }